@@ -1,5 +1,5 @@
 use std::{
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{
         Arc,
         mpsc::{self, RecvTimeoutError},
@@ -10,7 +10,7 @@ use std::{
 
 use auto_launch::AutoLaunch;
 use dashmap::DashMap;
-use muda::MenuEventReceiver;
+use muda::{MenuEvent, MenuEventReceiver, MenuId};
 use takeable::Takeable;
 use tao::{
     event::{Event, StartCause, WindowEvent},
@@ -21,13 +21,20 @@ use tray_icon::{Icon, TrayIcon};
 
 use crate::{
     errors::{AppResult, RedefaulterError},
-    platform::{AudioEndpointNotification, AudioNightmare, DeviceSet, Discovered},
+    event_trace::{EventRecorder, TraceSnapshot, TracedEvent},
+    platform::{
+        AudioEndpointNotification, AudioNightmare, ConfigDevice, DeviceRole, DeviceSet, Discovered,
+        IntegrationTargets,
+    },
     popups::{
-        FirstTimeChoice, first_time_popups, profile_exists_popup, settings_load_failed_popup,
+        FirstTimeChoice, NewProfileOptions, StartupChoice, first_time_popups, monitor_only_popup,
+        partial_apply_popup, profile_exists_popup, settings_load_failed_popup,
+        startup_report_popup,
     },
-    processes::{self, LockFile},
+    processes::{self, LockFile, Process},
     profiles::Profiles,
     settings::Settings,
+    timers::{TimerId, Timers},
     updates::{UpdateHandle, UpdateReply, UpdateState},
 };
 
@@ -40,9 +47,13 @@ pub enum CustomEvent {
     AudioEndpointNotification(AudioEndpointNotification),
     UpdateReply(UpdateReply),
     FirstTimeChoice(FirstTimeChoice),
-    NewProfile(PathBuf, bool),
+    StartupChoice(StartupChoice),
+    NewProfile(PathBuf, NewProfileOptions),
     ReloadProfiles,
+    TimerFired(TimerId),
     ExitRequested,
+    /// An entry from an event trace being replayed
+    Replayed(TracedEvent),
 }
 
 pub type AppEventProxy = EventLoopProxy<CustomEvent>;
@@ -50,7 +61,10 @@ pub type AppEventProxy = EventLoopProxy<CustomEvent>;
 pub struct App {
     pub endpoints: AudioNightmare,
     pub profiles: Profiles,
-    pub process_watcher_handle: Takeable<JoinHandle<AppResult<()>>>,
+    /// Running processes, shared with the process watcher and `Profiles`
+    pub processes: Arc<DashMap<u32, Process>>,
+    /// `None` when replaying, since processes come from the trace instead
+    pub process_watcher_handle: Option<JoinHandle<AppResult<()>>>,
     // TODO move out of App?
     pub current_defaults: DeviceSet<Discovered>,
 
@@ -75,37 +89,56 @@ pub struct App {
     // To prevent fighting with something else messing with devices
     // changes_within_few_seconds: usize,
     // last_change: Instant,
-    next_device_poll_at: Instant,
+    /// Named deadlines for the event loop to wake up for
+    pub timers: Timers,
+    /// When present, the `CustomEvent`s we handle get written to a trace file
+    pub event_recorder: Option<EventRecorder>,
+    /// When `true`, we're replaying an event trace against simulated devices.
+    ///
+    /// Timers, popups, and anything else reaching outside the app are left to the trace.
+    pub replaying: bool,
+    /// When `true`, we're waiting on the user to answer the startup report,
+    /// and shouldn't change any devices in the meantime.
+    pub awaiting_startup_choice: bool,
+    /// Changes shown to the user in the startup report, kept so that their answer
+    /// applies to exactly what they saw.
+    shown_startup_changes: Option<DeviceSet<Discovered>>,
+    /// Changes the user chose to skip on startup.
+    ///
+    /// Left alone until profiles or devices call for a different change.
+    skipped_startup_changes: Option<DeviceSet<Discovered>>,
+    /// Devices from active profiles that weren't connected when the profile activated.
+    ///
+    /// If `wait_for_devices` is enabled, we apply them the moment one shows up.
+    awaited_devices: Vec<(DeviceRole, ConfigDevice)>,
+    /// Roles that failed to apply on our last attempt
+    pub failed_roles: Vec<DeviceRole>,
 }
 
 // TODO check for wrestling with other apps
 
 impl App {
-    pub fn build(event_proxy: AppEventProxy) -> AppResult<Self> {
+    /// Given a snapshot from an event trace, devices and processes are taken from it
+    /// instead of the host, to replay the rest of the trace against.
+    pub fn build(event_proxy: AppEventProxy, replay: Option<TraceSnapshot>) -> AppResult<Self> {
         let processes = Arc::new(DashMap::new());
-        let (process_tx, process_rx) = mpsc::channel();
-        let map_clone = Arc::clone(&processes);
-        let proxy_clone = event_proxy.clone();
 
         let lock_file = LockFile::build()?;
 
-        let process_watcher_handle = thread::spawn(move || {
-            processes::process_event_loop(map_clone, process_tx, proxy_clone)
-        });
-
-        let initial_size = match process_rx.recv_timeout(Duration::from_secs(3)) {
-            Ok(size) => size,
-            Err(RecvTimeoutError::Timeout) => {
-                return Err(RedefaulterError::ProcessWatcherSetup("Timeout".to_string()));
+        let replaying = replay.is_some();
+        let (process_watcher_handle, platform_snapshot) = match replay {
+            Some(snapshot) => {
+                for process in snapshot.processes {
+                    processes.insert(process.process_id, process);
+                }
+                (None, Some(snapshot.platform))
             }
-            Err(RecvTimeoutError::Disconnected) => {
-                let result = process_watcher_handle.join();
-                let output = format!("{result:?}");
-                return Err(RedefaulterError::ProcessWatcherSetup(output));
+            None => {
+                let handle = Self::spawn_process_watcher(&processes, &event_proxy)?;
+                (Some(handle), None)
             }
         };
 
-        assert_eq!(initial_size, processes.len());
         let exe_path = std::env::current_exe()?;
         let config_name = exe_path.with_extension("toml");
         let config_name = config_name
@@ -134,18 +167,28 @@ impl App {
             }
         };
 
-        let endpoints =
-            AudioNightmare::build(Some(event_proxy.clone()), Some(&settings.devices.platform))?;
+        let endpoints = match platform_snapshot {
+            Some(snapshot) => {
+                AudioNightmare::build_simulated(&settings.devices.platform, snapshot)?
+            }
+            None => {
+                AudioNightmare::build(Some(event_proxy.clone()), Some(&settings.devices.platform))?
+            }
+        };
 
         debug!("Endpoints: {endpoints:?}");
 
+        if endpoints.is_monitor_only() {
+            monitor_only_popup();
+        }
+
         // let config_defaults = settings.platform.default_devices.clone();
 
         let current_defaults = endpoints.get_current_defaults()?;
 
         debug!("Current default devices: {current_defaults:?}");
 
-        let mut profiles = Profiles::build(processes)?;
+        let mut profiles = Profiles::build(Arc::clone(&processes))?;
 
         if let Err(e) = profiles.load_from_default_dir() {
             crate::popups::profile_load_failed_popup(e, event_proxy.clone());
@@ -153,7 +196,18 @@ impl App {
 
         let updates = UpdateHandle::new(event_proxy.clone());
 
-        let auto_launch = if let Some(path) = exe_path.to_str() {
+        // First time setup has its own prompts, no need to stack ours on top.
+        let awaiting_startup_choice =
+            settings.devices.confirm_on_startup && settings.misc.first_time_setup_done;
+
+        let mut timers = Timers::default();
+        // Check devices as soon as the event loop starts
+        timers.schedule_at(TimerId::DevicePoll, Instant::now());
+
+        // Not something a replay should be toggling
+        let auto_launch = if replaying {
+            None
+        } else if let Some(path) = exe_path.to_str() {
             auto_launch::AutoLaunchBuilder::new()
                 .set_app_name("redefaulter")
                 .set_app_path(path)
@@ -168,7 +222,8 @@ impl App {
             endpoints,
             profiles,
             update_state: UpdateState::Idle,
-            process_watcher_handle: Takeable::new(process_watcher_handle),
+            processes,
+            process_watcher_handle,
             // config_defaults,
             current_defaults,
             event_proxy,
@@ -181,20 +236,61 @@ impl App {
             update_icon: None,
             updates: Takeable::new(updates),
             auto_launch,
-            next_device_poll_at: Instant::now(),
+            timers,
+            event_recorder: None,
+            replaying,
+            awaiting_startup_choice,
+            shown_startup_changes: None,
+            skipped_startup_changes: None,
+            awaited_devices: Vec::new(),
+            failed_roles: Vec::new(),
         })
     }
+    /// Starts watching for processes on a new thread, returning once the initial list is filled in.
+    fn spawn_process_watcher(
+        processes: &Arc<DashMap<u32, Process>>,
+        event_proxy: &AppEventProxy,
+    ) -> AppResult<JoinHandle<AppResult<()>>> {
+        let (process_tx, process_rx) = mpsc::channel();
+        let map_clone = Arc::clone(processes);
+        let proxy_clone = event_proxy.clone();
+
+        let process_watcher_handle = thread::spawn(move || {
+            processes::process_event_loop(map_clone, process_tx, proxy_clone)
+        });
+
+        let initial_size = match process_rx.recv_timeout(Duration::from_secs(3)) {
+            Ok(size) => size,
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(RedefaulterError::ProcessWatcherSetup("Timeout".to_string()));
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                let result = process_watcher_handle.join();
+                let output = format!("{result:?}");
+                return Err(RedefaulterError::ProcessWatcherSetup(output));
+            }
+        };
+
+        assert_eq!(initial_size, processes.len());
+        Ok(process_watcher_handle)
+    }
     /// Given a list of profiles, will return the roles that need to be changed to fit the active profiles.
     ///
     /// Starting from the lowest priority, lays all of their desired devices
     /// on top of each other, discarding any devices that aren't connected to the system.
     ///
+    /// Roles the user has opted out of managing are never included.
+    ///
     /// Returns `None` if the resulting devices are the same as the current devices,
-    /// or if the user has actions temporarily paused.
+    /// if the user has actions temporarily paused,
+    /// or if we're unable to change devices on this system at all.
     pub fn get_damaged_devices(
         &self,
         mut only_config_default: bool,
     ) -> Option<DeviceSet<Discovered>> {
+        if self.endpoints.is_monitor_only() {
+            return None;
+        }
         // If the user has the pause override active, we shouldn't trigger
         // any device change actions.
         //
@@ -229,6 +325,7 @@ impl App {
         }
 
         // Clears device actions for roles that're already properly set
+        // (including unmanaged roles, since they're left as-is from `current_defaults`)
         self.endpoints
             .discard_healthy(&mut device_actions, &self.current_defaults);
 
@@ -238,36 +335,90 @@ impl App {
             Some(device_actions)
         }
     }
+    /// Starting from the config's targets, lays each active profile's integration targets
+    /// on top of each other, in the same priority order as their devices.
+    pub fn get_integration_targets(&self) -> IntegrationTargets {
+        let mut targets = self.settings.devices.platform.integrations.clone();
+
+        // Like with devices, paused actions and the preferred defaults override
+        // both leave the profiles out of it.
+        let temporary_override = &self.profiles.temporary_override;
+        if !temporary_override.is_paused() && !temporary_override.is_preferred_defaults() {
+            for profile_targets in self.profiles.iter_active_integration_targets() {
+                targets.overlay(profile_targets);
+            }
+        }
+
+        targets
+    }
+    /// Sends the current default devices out to any enabled integrations (like ShadowPlay).
+    pub fn dispatch_integrations(&self) {
+        let targets = self.get_integration_targets();
+        self.endpoints
+            .dispatch_integrations(&self.current_defaults, &targets);
+    }
     // TODO find more graceful way to do the initial/force update
     pub fn update_active_profiles(&mut self, force_update: bool) -> AppResult<()> {
         let profiles_changed = self.profiles.update_active_profiles(force_update);
         if profiles_changed {
+            self.arm_device_watcher();
+            // A newly (in)active profile might want integrations to follow a different role
+            self.dispatch_integrations();
             self.update_tray_menu()?;
         }
         Ok(())
     }
+    /// Takes note of any devices the active profiles want that aren't connected yet,
+    /// so we can react as soon as they appear.
+    pub fn arm_device_watcher(&mut self) {
+        self.awaited_devices.clear();
+        if !self.settings.devices.wait_for_devices {
+            return;
+        }
+        let fuzzy_match_names = self.settings.devices.fuzzy_match_names;
+        for profile in self.profiles.iter_active_override_sets() {
+            self.awaited_devices.extend(
+                self.endpoints
+                    .find_missing_devices(profile, fuzzy_match_names),
+            );
+        }
+        if !self.awaited_devices.is_empty() {
+            debug!("Waiting for devices: {:?}", self.awaited_devices);
+        }
+    }
+    /// Returns `true` if any device we were waiting for is now connected,
+    /// removing those that were found from the watch list.
+    fn awaited_device_connected(&mut self) -> bool {
+        let fuzzy_match_names = self.settings.devices.fuzzy_match_names;
+        let endpoints = &self.endpoints;
+        let before = self.awaited_devices.len();
+        self.awaited_devices.retain(|(role, device)| {
+            endpoints
+                .try_find_device(&role.into(), device, fuzzy_match_names)
+                .is_none()
+        });
+        self.awaited_devices.len() != before
+    }
     pub fn handle_tao_event(
         &mut self,
         event: Event<CustomEvent>,
         control_flow: &mut ControlFlow,
         menu_channel: &MenuEventReceiver,
     ) -> AppResult<()> {
-        if self.process_watcher_handle.is_finished() {
-            let result = self.process_watcher_handle.take().join();
+        if let Some(handle) = self
+            .process_watcher_handle
+            .take_if(|handle| handle.is_finished())
+        {
+            let result = handle.join();
             let output = format!("{result:?}");
             return Err(RedefaulterError::ProcessWatcher(output));
         }
 
-        let now = Instant::now();
-        if self.next_device_poll_at.saturating_duration_since(now) == Duration::ZERO {
-            let change_detected = self.update_defaults()?;
-            let action_taken = self.change_devices_if_needed()?;
-            if change_detected || action_taken {
-                debug!("Poll noticed change!");
-                // If defaults changed or if we did some changes, update the menu.
-                self.update_tray_menu()?;
+        // When replaying, the trace says when our timers fired
+        if !self.replaying {
+            for timer in self.timers.pop_expired(Instant::now()) {
+                self.handle_custom_event(CustomEvent::TimerFired(timer), control_flow)?;
             }
-            self.next_device_poll_at = Instant::now() + DEVICE_CHECK_INTERVAL;
         }
 
         match event {
@@ -279,11 +430,11 @@ impl App {
                 *control_flow = ControlFlow::WaitUntil(delay);
                 self.tray_menu = Some(self.build_tray_late()?);
                 self.update_active_profiles(true)?;
-                self.change_devices_if_needed()?;
-                if self.settings.updates.allow_checking_for_updates {
+                self.startup_reconciliation()?;
+                if self.settings.updates.allow_checking_for_updates && !self.replaying {
                     self.updates.query_latest();
                 }
-                if !self.settings.misc.first_time_setup_done {
+                if !self.settings.misc.first_time_setup_done && !self.replaying {
                     first_time_popups(
                         self.current_defaults.clone(),
                         self.event_proxy.clone(),
@@ -297,8 +448,7 @@ impl App {
                 self.handle_custom_event(event, control_flow)?;
                 debug!("Event handling took {:?}", t.elapsed());
             }
-            // Timeout for an audio device reaction finished waiting
-            // (nothing else right now uses WaitUntil)
+            // One of our timers' deadlines was reached, already handled above
             Event::NewEvents(StartCause::ResumeTimeReached { .. }) => (),
             Event::NewEvents(StartCause::WaitCancelled {
                 requested_resume: _requested_resume,
@@ -328,14 +478,16 @@ impl App {
         ) {
             return Ok(());
         } else {
-            // Otherwise, let's set the timer for the next audio device poll.
-            *control_flow = ControlFlow::WaitUntil(
-                now + self.next_device_poll_at.saturating_duration_since(now),
-            );
+            // Otherwise, let's wait until the next timer's deadline.
+            *control_flow = match self.timers.next_deadline() {
+                Some(deadline) if !self.replaying => ControlFlow::WaitUntil(deadline),
+                _ => ControlFlow::Wait,
+            };
         }
 
         while let Ok(event) = menu_channel.try_recv() {
             debug!("Menu Event: {event:?}");
+            self.record_event(TracedEvent::TrayMenu(event.id.as_ref().to_owned()));
             let t = Instant::now();
             self.handle_tray_menu_event(event, control_flow)?;
             debug!("Tray event handling took {:?}", t.elapsed());
@@ -349,12 +501,21 @@ impl App {
         event: CustomEvent,
         control_flow: &mut ControlFlow,
     ) -> AppResult<()> {
+        self.record_custom_event(&event);
         use CustomEvent::*;
         match event {
             // Platform notification about endpoint status
             AudioEndpointNotification(notif) => {
                 // Dispatch to our platform-specific handler
-                self.endpoints.handle_endpoint_notification(notif)?;
+                self.endpoints.handle_endpoint_notification(notif.clone())?;
+                // Recorded once handled, so a replay knows what device it added without asking Windows
+                if self.event_recorder.is_some() {
+                    let added = self.endpoints.added_endpoint(&notif);
+                    self.record_event(TracedEvent::AudioEndpointNotification {
+                        notification: notif,
+                        added,
+                    });
+                }
             }
             // Handler processed event, now we can react
             AudioEndpointUpdate => {
@@ -362,6 +523,20 @@ impl App {
                 // including when we set our desired devices' roles.
                 // So instead of reacting to each event instantly (which would cause even more noise we'd react to),
                 // we check the devices every few seconds and apply it on each cycle.
+                //
+                // The exception being a device we've been told to wait for,
+                // which is only reacted to once per connection.
+                if self.settings.devices.wait_for_devices {
+                    let connected = self.awaited_device_connected();
+                    // Re-arming in case one of the profiles' devices was just disconnected
+                    self.arm_device_watcher();
+                    if connected {
+                        info!("Awaited device connected, applying now!");
+                        self.update_defaults()?;
+                        self.change_devices_if_needed()?;
+                        self.update_tray_menu()?;
+                    }
+                }
             }
             // A process has opened or closed
             ProcessesChanged => {
@@ -374,24 +549,199 @@ impl App {
                 debug!("Reload Profiles event recieved!");
                 self.reload_profiles()?;
             }
+            TimerFired(timer) => {
+                self.handle_timer(timer)?;
+            }
             FirstTimeChoice(choice) => {
                 self.handle_first_time_choice(choice)?;
             }
+            StartupChoice(choice) => {
+                self.handle_startup_choice(choice)?;
+            }
             UpdateReply(reply) => {
                 debug!("Update Event: {reply:?}");
                 self.handle_update_reply(reply)?;
             }
-            NewProfile(process_path, save_absolute_path) => {
-                if let Err(e) = self.profiles.new_profile(process_path, save_absolute_path) {
+            NewProfile(process_path, options) => {
+                let mut override_set = DeviceSet::default();
+                if options.from_current_defaults {
+                    self.endpoints.copy_all_roles(
+                        &self.current_defaults,
+                        &mut override_set,
+                        self.settings.devices.fuzzy_match_names,
+                        self.settings.devices.save_guid,
+                    );
+                }
+                if let Err(e) = self.profiles.new_profile(
+                    process_path,
+                    options.save_absolute_path,
+                    override_set,
+                ) {
                     profile_exists_popup(e);
                     return Ok(());
                 };
                 self.update_active_profiles(false)?;
                 self.change_devices_if_needed()?;
             }
+            Replayed(event) => {
+                self.handle_replayed_event(event, control_flow)?;
+            }
         }
         Ok(())
     }
+    /// Starts writing the events we handle to a trace at the given path,
+    /// beginning with a snapshot of the current devices and processes.
+    pub fn start_recording(&mut self, path: &Path) -> AppResult<()> {
+        let recorder = EventRecorder::build(path, self.trace_snapshot()?)?;
+        self.event_recorder = Some(recorder);
+        Ok(())
+    }
+    fn trace_snapshot(&self) -> AppResult<TraceSnapshot> {
+        Ok(TraceSnapshot {
+            platform: self.endpoints.snapshot()?,
+            processes: self
+                .processes
+                .iter()
+                .map(|process| process.value().clone())
+                .collect(),
+        })
+    }
+    /// Writes the event to the trace, if we're recording one.
+    ///
+    /// Any failure stops the recording, since a trace with a gap in it can't be trusted to replay.
+    fn record_event(&mut self, event: TracedEvent) {
+        let Some(mut recorder) = self.event_recorder.take() else {
+            return;
+        };
+        let mut result = Ok(());
+        if recorder.is_full() {
+            result = self
+                .trace_snapshot()
+                .and_then(|snapshot| recorder.rotate(snapshot));
+        }
+        match result.and_then(|_| recorder.record(event)) {
+            Ok(()) => self.event_recorder = Some(recorder),
+            Err(e) => error!("Failed to record event, stopping recording: {e}"),
+        }
+    }
+    /// Records the event before it's handled, if we're recording a trace.
+    fn record_custom_event(&mut self, event: &CustomEvent) {
+        let Some(recorder) = self.event_recorder.as_mut() else {
+            return;
+        };
+        let traced = match event {
+            CustomEvent::ProcessesChanged => recorder.process_changes(&self.processes),
+            event => match TracedEvent::from_event(event) {
+                Some(traced) => traced,
+                None => return,
+            },
+        };
+        self.record_event(traced);
+    }
+    /// Sends an entry from an event trace through the same handler it went through when recorded.
+    fn handle_replayed_event(
+        &mut self,
+        event: TracedEvent,
+        control_flow: &mut ControlFlow,
+    ) -> AppResult<()> {
+        debug!("Replaying {event:?}");
+        let event = match event {
+            TracedEvent::ProcessesChanged { started, stopped } => {
+                for process_id in stopped {
+                    self.processes.remove(&process_id);
+                }
+                for process in started {
+                    self.processes.insert(process.process_id, process);
+                }
+                CustomEvent::ProcessesChanged
+            }
+            TracedEvent::AudioEndpointNotification {
+                notification,
+                added,
+            } => {
+                self.endpoints.expect_endpoint(added);
+                CustomEvent::AudioEndpointNotification(notification)
+            }
+            TracedEvent::AudioEndpointUpdate => CustomEvent::AudioEndpointUpdate,
+            TracedEvent::StartupChoice(choice) => CustomEvent::StartupChoice(choice),
+            TracedEvent::ReloadProfiles => CustomEvent::ReloadProfiles,
+            TracedEvent::TimerFired(timer) => CustomEvent::TimerFired(timer),
+            TracedEvent::ExitRequested => CustomEvent::ExitRequested,
+            TracedEvent::TrayMenu(id) => {
+                if !self.is_replayable_menu_id(&id) {
+                    debug!("Not replaying tray menu item: {id}");
+                    return Ok(());
+                }
+                let event = MenuEvent { id: MenuId(id) };
+                return self.handle_tray_menu_event(event, control_flow);
+            }
+            // Only there for context when reading the trace
+            TracedEvent::Snapshot(_) | TracedEvent::Other(_) => return Ok(()),
+        };
+        self.handle_custom_event(event, control_flow)
+    }
+    /// React to one of our timers' deadlines being reached.
+    fn handle_timer(&mut self, timer: TimerId) -> AppResult<()> {
+        match timer {
+            TimerId::DevicePoll => {
+                let change_detected = self.update_defaults()?;
+                let action_taken = self.change_devices_if_needed()?;
+                if change_detected || action_taken {
+                    debug!("Poll noticed change!");
+                    // If defaults changed or if we did some changes, update the menu.
+                    self.update_tray_menu()?;
+                }
+                self.timers
+                    .schedule_in(TimerId::DevicePoll, DEVICE_CHECK_INTERVAL);
+            }
+        }
+        Ok(())
+    }
+    /// Run once the event loop starts, either applies our desired devices right away,
+    /// or shows the user what's about to change if they've asked us to confirm first.
+    fn startup_reconciliation(&mut self) -> AppResult<()> {
+        if !self.awaiting_startup_choice {
+            self.change_devices_if_needed()?;
+            return Ok(());
+        }
+
+        // If there's nothing to report, everything's already where it should be.
+        if let Some(actions) = self.get_damaged_devices(false) {
+            // When replaying, the user's answer comes from the trace
+            if !self.replaying {
+                startup_report_popup(
+                    self.current_defaults.clone(),
+                    actions.clone(),
+                    self.event_proxy.clone(),
+                );
+            }
+            self.shown_startup_changes = Some(actions);
+        } else {
+            self.awaiting_startup_choice = false;
+            self.update_tray_menu()?;
+        }
+        Ok(())
+    }
+    fn handle_startup_choice(&mut self, choice: StartupChoice) -> AppResult<()> {
+        self.awaiting_startup_choice = false;
+        if !choice.ask_again {
+            self.settings.devices.confirm_on_startup = false;
+            self.settings.save(&self.config_path)?;
+        }
+        // Profiles or devices may have changed while the popup was open,
+        // but the user only answered for what they were shown.
+        if let Some(shown) = self.shown_startup_changes.take() {
+            if choice.apply {
+                self.apply_changes(shown)?;
+            } else {
+                self.skipped_startup_changes = Some(shown);
+            }
+        }
+        // Anything that changed since then gets handled like usual
+        self.change_devices_if_needed()?;
+        self.update_tray_menu()?;
+        Ok(())
+    }
     fn handle_first_time_choice(&mut self, choice: FirstTimeChoice) -> AppResult<()> {
         match choice {
             FirstTimeChoice::SetupFinished => {
@@ -429,26 +779,98 @@ impl App {
         Ok(())
     }
     /// Query the OS for the current default endpoints, returning `true` if a change occurred.
+    ///
+    /// Also keeps any integrations up to date with the new defaults.
     pub fn update_defaults(&mut self) -> AppResult<bool> {
         let incoming = self.endpoints.get_current_defaults()?;
         let changed = self.current_defaults != incoming;
         self.current_defaults = incoming;
+        // Tacking on integrations here, since this runs not too frequently
+        // (mainly only when devices change, or on each poll),
+        // but enough to not lag behind.
+        self.dispatch_integrations();
         Ok(changed)
     }
     pub fn change_devices_if_needed(&mut self) -> AppResult<bool> {
+        if self.awaiting_startup_choice {
+            return Ok(false);
+        }
         if let Some(actions) = self.get_damaged_devices(false) {
-            self.endpoints.change_devices(actions)?;
-            self.update_defaults()?;
-            Ok(true)
+            if self.only_skipped_changes(&actions) {
+                return Ok(false);
+            }
+            self.apply_changes(actions)
         } else {
+            // Nothing left to change, so nothing can be failing either.
+            self.handle_apply_result(Ok(()))?;
             Ok(false)
         }
     }
+    /// Sets the given devices, returning `true` if anything worth showing changed.
+    fn apply_changes(&mut self, actions: DeviceSet<Discovered>) -> AppResult<bool> {
+        let result = self.endpoints.change_devices(actions);
+        let defaults_changed = self.update_defaults()?;
+        let result_changed = self.handle_apply_result(result)?;
+        Ok(defaults_changed || result_changed)
+    }
+    /// Returns `true` if every change in `actions` was already skipped by the user on startup.
+    ///
+    /// Once something else needs changing, the skip is forgotten.
+    fn only_skipped_changes(&mut self, actions: &DeviceSet<Discovered>) -> bool {
+        let Some(skipped) = self.skipped_startup_changes.as_ref() else {
+            return false;
+        };
+        let all_skipped = DeviceRole::ALL.iter().all(|role| {
+            actions.get_role(role).is_none() || actions.get_role(role) == skipped.get_role(role)
+        });
+        if !all_skipped {
+            info!("Profiles or devices changed since startup changes were skipped, applying again");
+            self.skipped_startup_changes = None;
+        }
+        all_skipped
+    }
+    /// Keeps track of which roles failed to apply, letting the user know when that changes.
+    ///
+    /// Returns `false` if the same roles failed as last time, since there's nothing new to show.
+    ///
+    /// Any error other than a partial apply is passed along.
+    fn handle_apply_result(&mut self, result: AppResult<()>) -> AppResult<bool> {
+        match result {
+            Ok(()) => {
+                if !self.failed_roles.is_empty() {
+                    self.failed_roles.clear();
+                    self.update_tray_menu()?;
+                }
+                Ok(true)
+            }
+            Err(RedefaulterError::PartialApply(failures)) => {
+                let roles: Vec<DeviceRole> =
+                    failures.iter().map(|(role, _)| role.clone()).collect();
+                // We retry on each poll, no need to nag the user with the same failures each time.
+                if roles == self.failed_roles {
+                    return Ok(false);
+                }
+                self.failed_roles = roles;
+                let error = RedefaulterError::PartialApply(failures);
+                error!("{error}");
+                partial_apply_popup(error);
+                self.update_tray_menu()?;
+                Ok(true)
+            }
+            Err(e) => Err(e),
+        }
+    }
     /// Meant to be run on shutdown (via error or user request) to attempt to set the default devices back
     /// to the global defaults defined in the config.
     pub fn back_to_default(&self) -> AppResult<()> {
         if let Some(actions) = self.get_damaged_devices(true) {
-            self.endpoints.change_devices(actions)?;
+            match self.endpoints.change_devices(actions) {
+                // We're on the way out, so the best we can do is make note of it.
+                Err(RedefaulterError::PartialApply(failures)) => {
+                    error!("{}", RedefaulterError::PartialApply(failures));
+                }
+                result => result?,
+            }
         }
         Ok(())
     }
@@ -459,6 +881,9 @@ impl App {
             return Ok(());
         };
         self.update_active_profiles(false)?;
+        // Profiles' contents may have changed without the active set changing
+        self.arm_device_watcher();
+        self.dispatch_integrations();
         self.change_devices_if_needed()?;
         self.update_tray_menu()?;
         Ok(())
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use argh::FromArgs;
 
 // TODO Command for checking overrides once then exiting
@@ -14,6 +16,7 @@ pub struct TopLevelCmd {
 pub enum SubCommands {
     List(ListSubcommand),
     Tui(Tui),
+    Replay(ReplaySubcommand),
 }
 
 #[derive(FromArgs, PartialEq, Debug)]
@@ -31,6 +34,18 @@ pub struct ListSubcommand {
     pub profile_format: bool,
 }
 
+#[derive(FromArgs, PartialEq, Debug)]
+/// Replay a recorded event trace against simulated audio devices
+#[argh(subcommand, name = "replay")]
+pub struct ReplaySubcommand {
+    #[argh(positional)]
+    /// path to the event trace
+    pub path: PathBuf,
+    #[argh(switch, short = 'f')]
+    /// replay events back-to-back instead of at their recorded pace
+    pub fast: bool,
+}
+
 #[derive(FromArgs, PartialEq, Debug)]
 /// Allow configuration with a TUI
 #[argh(subcommand, name = "tui")]
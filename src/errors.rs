@@ -1,6 +1,8 @@
 // use color_eyre::Result;
 
-use std::ffi::OsString;
+use std::{ffi::OsString, path::PathBuf};
+
+use crate::platform::DeviceRole;
 
 pub type AppResult<T> = Result<T, RedefaulterError>;
 
@@ -14,6 +16,8 @@ pub enum RedefaulterError {
     Wasapi(#[from] wasapi::WasapiError),
     #[error("IO Error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("JSON Error: {0}")]
+    Json(#[from] serde_json::Error),
     #[error("TOML Serialization Error: {0}")]
     TomlSer(#[from] toml::ser::Error),
     #[error("TOML Deserialization Error: {0}")]
@@ -41,6 +45,10 @@ pub enum RedefaulterError {
     AutoLaunchInit,
     #[error("ShadowPlay Error: {0}")]
     ShadowPlay(#[from] shadowplay::Error),
+    #[error("Unable to change default devices on this system, running in monitor-only mode")]
+    MonitorOnly,
+    #[error("Failed to set some default devices!\n{}", format_role_errors(.0))]
+    PartialApply(Vec<(DeviceRole, RedefaulterError)>),
     #[error("Device not found: {0}")]
     DeviceNotFound(String),
     #[error("Profile not found: {0:?}")]
@@ -83,4 +91,14 @@ pub enum RedefaulterError {
     BadChecksum,
     #[error("Tried to update non-portable app")]
     NotPortable,
+    #[error("Event trace doesn't start with a snapshot: {0:?}")]
+    InvalidTrace(PathBuf),
+}
+
+fn format_role_errors(errors: &[(DeviceRole, RedefaulterError)]) -> String {
+    errors
+        .iter()
+        .map(|(role, e)| format!("{role}: {e}"))
+        .collect::<Vec<_>>()
+        .join("\n")
 }
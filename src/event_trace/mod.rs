@@ -0,0 +1,365 @@
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use dashmap::DashMap;
+use fs_err::{self as fs, File};
+use serde::{Deserialize, Serialize};
+use tracing::*;
+
+use crate::{
+    app::{AppEventProxy, CustomEvent},
+    errors::{AppResult, RedefaulterError},
+    platform::{AudioEndpointNotification, PlatformSnapshot, ResolvedDevice},
+    popups::StartupChoice,
+    processes::Process,
+    timers::TimerId,
+};
+
+/// Once a trace grows past this, it's moved aside and a new one is started
+/// from a fresh snapshot, like the log file.
+const MAX_TRACE_SIZE: u64 = 1024 * 1024 * 5;
+
+/// Everything a replay needs to pick up from where a trace starts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceSnapshot {
+    pub platform: PlatformSnapshot,
+    pub processes: Vec<Process>,
+}
+
+/// A serializable mirror of [`CustomEvent`], plus the tray menu items clicked.
+///
+/// Events that carry payloads we can't reproduce (update checks, file pickers, first time setup)
+/// are kept as their `Debug` output, for context when reading a trace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TracedEvent {
+    /// Always the first entry of a trace, the state the rest of it builds on
+    Snapshot(TraceSnapshot),
+    /// Processes that started or stopped since the last entry
+    ProcessesChanged {
+        started: Vec<Process>,
+        stopped: Vec<u32>,
+    },
+    AudioEndpointUpdate,
+    /// Recorded once handled, along with the device it added, if any
+    AudioEndpointNotification {
+        notification: AudioEndpointNotification,
+        added: Option<ResolvedDevice>,
+    },
+    StartupChoice(StartupChoice),
+    ReloadProfiles,
+    TimerFired(TimerId),
+    ExitRequested,
+    /// ID of the tray menu item that was clicked
+    TrayMenu(String),
+    Other(String),
+}
+
+impl TracedEvent {
+    /// Returns the traced form of the event, or `None` if it takes more than the event to record.
+    ///
+    /// Changed processes are diffed by `EventRecorder::process_changes`,
+    /// and endpoint notifications are recorded by the `App` once they're handled.
+    pub fn from_event(event: &CustomEvent) -> Option<Self> {
+        let traced = match event {
+            CustomEvent::ProcessesChanged
+            | CustomEvent::AudioEndpointNotification(_)
+            | CustomEvent::Replayed(_) => return None,
+            CustomEvent::AudioEndpointUpdate => Self::AudioEndpointUpdate,
+            CustomEvent::StartupChoice(choice) => Self::StartupChoice(*choice),
+            CustomEvent::ReloadProfiles => Self::ReloadProfiles,
+            CustomEvent::TimerFired(timer) => Self::TimerFired(*timer),
+            CustomEvent::ExitRequested => Self::ExitRequested,
+            other => Self::Other(format!("{other:?}")),
+        };
+        Some(traced)
+    }
+}
+
+/// A single line of an event trace file.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TraceEntry {
+    /// Milliseconds since the recording started
+    pub elapsed_ms: u64,
+    /// Milliseconds since the Unix epoch, to help line up traces with logs
+    pub unix_ms: u64,
+    pub event: TracedEvent,
+}
+
+/// A trace read back from a file, ready to be replayed.
+#[derive(Debug)]
+pub struct Trace {
+    pub snapshot: TraceSnapshot,
+    /// Every entry after the snapshot
+    pub entries: Vec<TraceEntry>,
+}
+
+/// Writes handled [`CustomEvent`]s to a file as JSON lines.
+#[derive(Debug)]
+pub struct EventRecorder {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    /// Bytes written to the current file
+    written: u64,
+    max_size: u64,
+    started: Instant,
+    /// Processes as of the last entry, so only what changed gets recorded
+    known_processes: BTreeMap<u32, Process>,
+}
+
+impl EventRecorder {
+    /// Creates (or truncates) the trace file at the given path, starting it off with the snapshot.
+    pub fn build(path: &Path, snapshot: TraceSnapshot) -> AppResult<Self> {
+        let file = File::create(path)?;
+        info!("Recording events to {path:?}");
+        let mut recorder = Self {
+            path: path.to_owned(),
+            writer: BufWriter::new(file),
+            written: 0,
+            max_size: MAX_TRACE_SIZE,
+            started: Instant::now(),
+            known_processes: BTreeMap::new(),
+        };
+        recorder.write_snapshot(snapshot)?;
+        Ok(recorder)
+    }
+    /// Returns `true` once the current file is due to be rotated.
+    pub fn is_full(&self) -> bool {
+        self.written >= self.max_size
+    }
+    /// Moves the current file aside (replacing the last one moved aside),
+    /// and starts a new one off with the given snapshot so it can be replayed on its own.
+    pub fn rotate(&mut self, snapshot: TraceSnapshot) -> AppResult<()> {
+        self.writer.flush()?;
+        fs::rename(&self.path, rotated_path(&self.path))?;
+        self.writer = BufWriter::new(File::create(&self.path)?);
+        self.written = 0;
+        self.write_snapshot(snapshot)
+    }
+    pub fn record(&mut self, event: TracedEvent) -> AppResult<()> {
+        let unix_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let entry = TraceEntry {
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+            unix_ms,
+            event,
+        };
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        self.writer.write_all(&line)?;
+        // Flushing each event, since the traces are most useful right when something goes wrong
+        self.writer.flush()?;
+        self.written += line.len() as u64;
+        Ok(())
+    }
+    /// Returns the processes that started or stopped since the last entry.
+    pub fn process_changes(&mut self, processes: &DashMap<u32, Process>) -> TracedEvent {
+        let stopped = self
+            .known_processes
+            .keys()
+            .filter(|process_id| !processes.contains_key(*process_id))
+            .copied()
+            .collect();
+        let started = processes
+            .iter()
+            .filter(|process| !self.known_processes.contains_key(process.key()))
+            .map(|process| process.value().clone())
+            .collect();
+        self.known_processes = processes
+            .iter()
+            .map(|process| (*process.key(), process.value().clone()))
+            .collect();
+        TracedEvent::ProcessesChanged { started, stopped }
+    }
+    fn write_snapshot(&mut self, snapshot: TraceSnapshot) -> AppResult<()> {
+        self.known_processes = snapshot
+            .processes
+            .iter()
+            .map(|process| (process.process_id, process.clone()))
+            .collect();
+        self.record(TracedEvent::Snapshot(snapshot))
+    }
+}
+
+/// Reads the trace at the given path, which has to start with a snapshot.
+pub fn read_trace(path: &Path) -> AppResult<Trace> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut entries = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        // A trace cut off mid-write is still worth replaying up to that point
+        match serde_json::from_str::<TraceEntry>(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => {
+                warn!("Stopping at unreadable trace entry: {e}");
+                break;
+            }
+        }
+    }
+    let mut entries = entries.into_iter();
+    let Some(TraceEntry {
+        event: TracedEvent::Snapshot(snapshot),
+        ..
+    }) = entries.next()
+    else {
+        return Err(RedefaulterError::InvalidTrace(path.to_owned()));
+    };
+    Ok(Trace {
+        snapshot,
+        entries: entries.collect(),
+    })
+}
+
+/// Sends each entry to the event loop from a new thread,
+/// at the pace they were recorded unless `fast` is set.
+pub fn start_replay(entries: Vec<TraceEntry>, fast: bool, event_proxy: AppEventProxy) {
+    thread::spawn(move || {
+        let mut last_ms = entries.first().map(|entry| entry.elapsed_ms);
+        for entry in entries {
+            if !fast && let Some(last_ms) = last_ms {
+                thread::sleep(Duration::from_millis(
+                    entry.elapsed_ms.saturating_sub(last_ms),
+                ));
+            }
+            last_ms = Some(entry.elapsed_ms);
+            if event_proxy
+                .send_event(CustomEvent::Replayed(entry.event))
+                .is_err()
+            {
+                return;
+            }
+        }
+        info!("Finished replaying event trace");
+    });
+}
+
+/// Returns the path event traces are recorded to by default, next to the log file.
+pub fn default_trace_path() -> AppResult<PathBuf> {
+    let exe_path = std::env::current_exe()?;
+    let trace_name = exe_path
+        .with_extension("events.jsonl")
+        .file_name()
+        .expect("Couldn't build event trace path!")
+        .to_owned();
+    Ok(PathBuf::from(trace_name))
+}
+
+/// Returns where a full trace is moved to, `redefaulter.events.jsonl` -> `redefaulter.events.old.jsonl`.
+fn rotated_path(path: &Path) -> PathBuf {
+    path.with_extension("old.jsonl")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::platform::DeviceSet;
+
+    fn process(process_id: u32, name: &str) -> Process {
+        Process {
+            process_id,
+            name: PathBuf::from(name),
+            executable_path: None,
+        }
+    }
+
+    fn snapshot(processes: Vec<Process>) -> TraceSnapshot {
+        TraceSnapshot {
+            platform: PlatformSnapshot {
+                playback_devices: BTreeMap::new(),
+                recording_devices: BTreeMap::new(),
+                defaults: DeviceSet::default(),
+            },
+            processes,
+        }
+    }
+
+    #[test]
+    fn trace_reads_back_in_order() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("test.events.jsonl");
+        let mut recorder =
+            EventRecorder::build(&path, snapshot(vec![process(1, "a.exe")])).unwrap();
+
+        let processes = DashMap::new();
+        processes.insert(2, process(2, "b.exe"));
+        let changes = recorder.process_changes(&processes);
+        recorder.record(changes).unwrap();
+        recorder
+            .record(TracedEvent::TimerFired(TimerId::DevicePoll))
+            .unwrap();
+        recorder
+            .record(TracedEvent::TrayMenu("quit".to_string()))
+            .unwrap();
+
+        let trace = read_trace(&path).unwrap();
+        assert_eq!(trace.snapshot.processes.len(), 1);
+        assert_eq!(trace.entries.len(), 3);
+        let TracedEvent::ProcessesChanged { started, stopped } = &trace.entries[0].event else {
+            panic!("Expected process changes, got {:?}", trace.entries[0].event);
+        };
+        assert_eq!(started.len(), 1);
+        assert_eq!(started[0].process_id, 2);
+        assert_eq!(stopped, &[1]);
+        assert!(matches!(
+            trace.entries[1].event,
+            TracedEvent::TimerFired(TimerId::DevicePoll)
+        ));
+        assert!(matches!(&trace.entries[2].event, TracedEvent::TrayMenu(id) if id == "quit"));
+    }
+
+    #[test]
+    fn rotated_trace_starts_with_snapshot() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("test.events.jsonl");
+        let mut recorder = EventRecorder::build(&path, snapshot(Vec::new())).unwrap();
+        recorder.max_size = 1;
+        recorder.record(TracedEvent::ReloadProfiles).unwrap();
+        assert!(recorder.is_full());
+
+        recorder
+            .rotate(snapshot(vec![process(3, "c.exe")]))
+            .unwrap();
+        recorder.record(TracedEvent::ExitRequested).unwrap();
+
+        let old = read_trace(&rotated_path(&path)).unwrap();
+        assert!(matches!(
+            old.entries[..],
+            [TraceEntry {
+                event: TracedEvent::ReloadProfiles,
+                ..
+            }]
+        ));
+        let new = read_trace(&path).unwrap();
+        assert_eq!(new.snapshot.processes[0].process_id, 3);
+        assert!(matches!(
+            new.entries[..],
+            [TraceEntry {
+                event: TracedEvent::ExitRequested,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn trace_without_snapshot_rejected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("test.events.jsonl");
+        let entry = TraceEntry {
+            elapsed_ms: 0,
+            unix_ms: 0,
+            event: TracedEvent::ReloadProfiles,
+        };
+        fs::write(&path, serde_json::to_string(&entry).unwrap()).unwrap();
+        assert!(matches!(
+            read_trace(&path),
+            Err(RedefaulterError::InvalidTrace(_))
+        ));
+    }
+}
@@ -1,6 +1,7 @@
 #![deny(unused_must_use)]
 
 mod app;
+mod event_trace;
 mod panic_handler;
 mod platform;
 mod popups;
@@ -8,6 +9,7 @@ mod processes;
 mod profiles;
 mod settings;
 mod structs;
+mod timers;
 mod tray_menu;
 mod updates;
 
@@ -37,6 +39,14 @@ use tao::event_loop::EventLoopBuilder;
 pub fn run(args: TopLevelCmd) -> Result<()> {
     panic_handler::initialize_panic_handler()?;
     let ansi_support = enable_ansi_support::enable_ansi_support().is_ok();
+    // Resolved before the working directory changes out from under a relative path
+    let replay = match args.subcommand.as_ref() {
+        Some(args::SubCommands::Replay(replay)) => Some(args::ReplaySubcommand {
+            path: fs::canonicalize(&replay.path)?,
+            fast: replay.fast,
+        }),
+        _ => None,
+    };
     let working_directory = determine_working_directory().ok_or(RedefaulterError::WorkDir)?;
     if !working_directory.exists() {
         fs::create_dir(&working_directory)?;
@@ -93,9 +103,16 @@ pub fn run(args: TopLevelCmd) -> Result<()> {
                 return Ok(());
             }
             args::SubCommands::Tui(_) => todo!(),
+            // Runs the app like usual, just against the trace
+            args::SubCommands::Replay(_) => (),
         }
     }
 
+    let trace = match replay.as_ref() {
+        Some(replay) => Some(event_trace::read_trace(&replay.path)?),
+        None => None,
+    };
+
     let event_loop = EventLoopBuilder::<CustomEvent>::with_user_event().build();
     let event_proxy = event_loop.create_proxy();
 
@@ -104,7 +121,8 @@ pub fn run(args: TopLevelCmd) -> Result<()> {
     info!("Starting app... v{}", env!("CARGO_PKG_VERSION"));
 
     // Might need to catch more than just App::build's errors, but this is good enough for now.
-    let mut app = match App::build(event_proxy) {
+    let replay_snapshot = trace.as_ref().map(|trace| trace.snapshot.clone());
+    let mut app = match App::build(event_proxy.clone(), replay_snapshot) {
         Ok(app) => app,
         Err(e) => {
             error!("Failed to build App: {e}");
@@ -112,6 +130,15 @@ pub fn run(args: TopLevelCmd) -> Result<()> {
         }
     };
 
+    if let (Some(trace), Some(replay)) = (trace, replay) {
+        info!("Replaying event trace {:?}", replay.path);
+        event_trace::start_replay(trace.entries, replay.fast, event_proxy);
+    } else if app.settings.misc.record_events
+        && let Err(e) = event_trace::default_trace_path().and_then(|p| app.start_recording(&p))
+    {
+        error!("Failed to start recording events: {e}");
+    }
+
     // Attempts to detach the console window and hide it if it's present.
     // TODO Move into config option
     // #[cfg(windows)]
@@ -2,8 +2,11 @@
 mod windows;
 #[cfg(target_os = "windows")]
 pub use windows::{
-    AudioNightmare, ConfigDevice, DeviceRole, DeviceSet, DiscoveredDevice, PlatformSettings,
+    AudioNightmare, ConfigDevice, DeviceRole, DeviceSet, DiscoveredDevice, IntegrationTargets,
+    PlatformSettings,
     device_notifications::WindowsAudioNotification as AudioEndpointNotification,
+    simulated::{PlatformSnapshot, ResolvedDevice},
+    sorted_devices,
 };
 
 use serde::{Deserialize, Serialize};
@@ -19,7 +22,7 @@ use serde::{Deserialize, Serialize};
 /// A device tagged with this could be unreachable, and thus
 /// needs to be checked before setting any role to it.
 pub struct ConfigEntry;
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 /// A device tagged with this is known to be connected and available
 pub struct Discovered;
 
@@ -1,5 +1,6 @@
 // "Inspired" by https://github.com/fmsyt/output-switcher/blob/1528d44747793ab4e42d23761e021976a3113d98/src-tauri/src/ipc/audio/notifier.rs#L25
 
+use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 use wasapi::{Direction, Role};
 use windows::{
@@ -22,13 +23,15 @@ fn to_win_error<E: Debug>(e: E, code: WIN32_ERROR) -> windows::core::Error {
     windows::core::Error::new::<String>(code.to_hresult(), format!("{e:?}"))
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 // #[allow(non_camel_case_types)]
 pub enum WindowsAudioNotification {
     DefaultDeviceChanged {
-        _id: String,
-        _flow: Direction,
-        _role: Role,
+        id: String,
+        #[serde(with = "direction_serde")]
+        flow: Direction,
+        #[serde(with = "role_serde")]
+        role: Role,
     },
     DeviceAdded {
         id: String,
@@ -38,10 +41,59 @@ pub enum WindowsAudioNotification {
     },
     DeviceStateChanged {
         id: String,
-        state: DEVICE_STATE,
+        /// Raw `DEVICE_STATE` value
+        state: u32,
     },
 }
 
+// wasapi's types don't implement serde's traits, so these are needed for event traces.
+pub(super) mod direction_serde {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+    use wasapi::Direction;
+
+    pub fn serialize<S: Serializer>(
+        direction: &Direction,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let text = match direction {
+            Direction::Render => "Render",
+            Direction::Capture => "Capture",
+        };
+        serializer.serialize_str(text)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Direction, D::Error> {
+        match String::deserialize(deserializer)?.as_str() {
+            "Render" => Ok(Direction::Render),
+            "Capture" => Ok(Direction::Capture),
+            other => Err(D::Error::custom(format!("Unknown direction: {other}"))),
+        }
+    }
+}
+
+mod role_serde {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+    use wasapi::Role;
+
+    pub fn serialize<S: Serializer>(role: &Role, serializer: S) -> Result<S::Ok, S::Error> {
+        let text = match role {
+            Role::Console => "Console",
+            Role::Multimedia => "Multimedia",
+            Role::Communications => "Communications",
+        };
+        serializer.serialize_str(text)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Role, D::Error> {
+        match String::deserialize(deserializer)?.as_str() {
+            "Console" => Ok(Role::Console),
+            "Multimedia" => Ok(Role::Multimedia),
+            "Communications" => Ok(Role::Communications),
+            other => Err(D::Error::custom(format!("Unknown role: {other}"))),
+        }
+    }
+}
+
 #[implement(IMMNotificationClient)]
 #[allow(non_camel_case_types)]
 // Bit of a circular dependency, not a fan.
@@ -63,7 +115,7 @@ impl IMMNotificationClient_Impl for AppEventHandlerClient_Impl {
                         id: pwstrdeviceid
                             .to_string()
                             .map_err(|e| to_win_error(e, ERROR_INVALID_DATA))?,
-                        state: dwnewstate,
+                        state: dwnewstate.0,
                     },
                 ))
                 .map_err(|e| to_win_error(e, ERROR_ACCESS_DENIED))?;
@@ -129,11 +181,7 @@ impl IMMNotificationClient_Impl for AppEventHandlerClient_Impl {
             let role = Role::try_from(role).map_err(|e| to_win_error(e, ERROR_INVALID_DATA))?;
             self.0
                 .send_event(CustomEvent::AudioEndpointNotification(
-                    WindowsAudioNotification::DefaultDeviceChanged {
-                        _id: id,
-                        _flow: flow,
-                        _role: role,
-                    },
+                    WindowsAudioNotification::DefaultDeviceChanged { id, flow, role },
                 ))
                 .map_err(|e| to_win_error(e, ERROR_ACCESS_DENIED))?;
         }
@@ -186,3 +234,45 @@ impl NotificationCallbacks {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_device_changed_round_trip() {
+        let directions = [Direction::Render, Direction::Capture];
+        let roles = [Role::Console, Role::Multimedia, Role::Communications];
+        for direction in directions {
+            for role in roles {
+                let notification = WindowsAudioNotification::DefaultDeviceChanged {
+                    id: "{0.0.0.00000000}.{guid}".to_string(),
+                    flow: direction,
+                    role,
+                };
+                let text = serde_json::to_string(&notification).unwrap();
+                let parsed: WindowsAudioNotification = serde_json::from_str(&text).unwrap();
+                let WindowsAudioNotification::DefaultDeviceChanged {
+                    id,
+                    flow,
+                    role: parsed_role,
+                } = parsed
+                else {
+                    panic!("Wrong variant from {text}");
+                };
+                assert_eq!(id, "{0.0.0.00000000}.{guid}");
+                assert_eq!(flow, direction);
+                assert_eq!(parsed_role, role);
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_direction_and_role_rejected() {
+        let bad_direction =
+            r#"{"DefaultDeviceChanged":{"id":"x","flow":"Sideways","role":"Console"}}"#;
+        let bad_role = r#"{"DefaultDeviceChanged":{"id":"x","flow":"Render","role":"Boss"}}"#;
+        assert!(serde_json::from_str::<WindowsAudioNotification>(bad_direction).is_err());
+        assert!(serde_json::from_str::<WindowsAudioNotification>(bad_role).is_err());
+    }
+}
@@ -79,10 +79,18 @@ impl TryFrom<wasapi::Device> for DiscoveredDevice {
 pub struct DeviceSet<State> {
     #[serde(default)]
     pub playback: Option<WindowsAudioDevice<State>>,
+    /// Only used when the Multimedia role is split from the Console role
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub playback_media: Option<WindowsAudioDevice<State>>,
     #[serde(default)]
     pub playback_comms: Option<WindowsAudioDevice<State>>,
     #[serde(default)]
     pub recording: Option<WindowsAudioDevice<State>>,
+    /// Only used when the Multimedia role is split from the Console role
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recording_media: Option<WindowsAudioDevice<State>>,
     #[serde(default)]
     pub recording_comms: Option<WindowsAudioDevice<State>>,
 }
@@ -96,8 +104,10 @@ impl<State> DeviceSet<State> {
         use DeviceRole::*;
         match role {
             Playback => self.playback = new_device,
+            PlaybackMedia => self.playback_media = new_device,
             PlaybackComms => self.playback_comms = new_device,
             Recording => self.recording = new_device,
+            RecordingMedia => self.recording_media = new_device,
             RecordingComms => self.recording_comms = new_device,
         }
     }
@@ -105,8 +115,10 @@ impl<State> DeviceSet<State> {
         use DeviceRole::*;
         _ = match role {
             Playback => self.playback.take(),
+            PlaybackMedia => self.playback_media.take(),
             PlaybackComms => self.playback_comms.take(),
             Recording => self.recording.take(),
+            RecordingMedia => self.recording_media.take(),
             RecordingComms => self.recording_comms.take(),
         };
     }
@@ -114,8 +126,10 @@ impl<State> DeviceSet<State> {
         use DeviceRole::*;
         match role {
             Playback => self.playback.as_ref(),
+            PlaybackMedia => self.playback_media.as_ref(),
             PlaybackComms => self.playback_comms.as_ref(),
             Recording => self.recording.as_ref(),
+            RecordingMedia => self.recording_media.as_ref(),
             RecordingComms => self.recording_comms.as_ref(),
         }
     }
@@ -135,18 +149,60 @@ impl<State> DeviceSet<State> {
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DeviceRole {
+    /// Console role, and Multimedia role unless it's been split off
     Playback,
+    /// Multimedia role, when split from the Console role
+    PlaybackMedia,
     PlaybackComms,
+    /// Console role, and Multimedia role unless it's been split off
     Recording,
+    /// Multimedia role, when split from the Console role
+    RecordingMedia,
     RecordingComms,
 }
 
+impl DeviceRole {
+    /// Every role Redefaulter knows how to manage, in menu order.
+    pub const ALL: [DeviceRole; 6] = [
+        DeviceRole::Playback,
+        DeviceRole::PlaybackMedia,
+        DeviceRole::PlaybackComms,
+        DeviceRole::Recording,
+        DeviceRole::RecordingMedia,
+        DeviceRole::RecordingComms,
+    ];
+    /// Returns `true` for the roles that only exist when Multimedia is split from Console.
+    pub fn is_media(&self) -> bool {
+        matches!(self, Self::PlaybackMedia | Self::RecordingMedia)
+    }
+    /// Returns `true` for the Communications roles.
+    pub fn is_comms(&self) -> bool {
+        matches!(self, Self::PlaybackComms | Self::RecordingComms)
+    }
+    /// Returns the role Windows' direction and role pair is kept in.
+    ///
+    /// The Multimedia role always maps to a `*Media` role, even when it isn't split.
+    pub fn from_windows(direction: &wasapi::Direction, role: &wasapi::Role) -> Self {
+        use wasapi::{Direction::*, Role::*};
+        match (direction, role) {
+            (Render, Console) => Self::Playback,
+            (Render, Multimedia) => Self::PlaybackMedia,
+            (Render, Communications) => Self::PlaybackComms,
+            (Capture, Console) => Self::Recording,
+            (Capture, Multimedia) => Self::RecordingMedia,
+            (Capture, Communications) => Self::RecordingComms,
+        }
+    }
+}
+
 impl Display for DeviceRole {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let role_str = match self {
             Self::Playback => "Playback",
+            Self::PlaybackMedia => "Playback Media",
             Self::PlaybackComms => "Playback Comm.",
             Self::Recording => "Recording",
+            Self::RecordingMedia => "Recording Media",
             Self::RecordingComms => "Recording Comm.",
         };
         write!(f, "{role_str}")
@@ -155,9 +211,10 @@ impl Display for DeviceRole {
 
 impl From<&DeviceRole> for wasapi::Direction {
     fn from(value: &DeviceRole) -> Self {
+        use DeviceRole::*;
         match value {
-            DeviceRole::Playback | DeviceRole::PlaybackComms => Self::Render,
-            DeviceRole::Recording | DeviceRole::RecordingComms => Self::Capture,
+            Playback | PlaybackMedia | PlaybackComms => Self::Render,
+            Recording | RecordingMedia | RecordingComms => Self::Capture,
         }
     }
 }
@@ -170,9 +227,11 @@ impl From<DeviceRole> for wasapi::Direction {
 
 impl From<&DeviceRole> for wasapi::Role {
     fn from(value: &DeviceRole) -> Self {
+        use DeviceRole::*;
         match value {
-            DeviceRole::Playback | DeviceRole::Recording => Self::Console,
-            DeviceRole::PlaybackComms | DeviceRole::RecordingComms => Self::Communications,
+            Playback | Recording => Self::Console,
+            PlaybackMedia | RecordingMedia => Self::Multimedia,
+            PlaybackComms | RecordingComms => Self::Communications,
         }
     }
 }
@@ -199,8 +258,10 @@ impl<State> Display for WindowsAudioDevice<State> {
 impl<State> DeviceSet<State> {
     pub fn is_none(&self) -> bool {
         self.playback.is_none()
+            && self.playback_media.is_none()
             && self.playback_comms.is_none()
             && self.recording.is_none()
+            && self.recording_media.is_none()
             && self.recording_comms.is_none()
     }
 }
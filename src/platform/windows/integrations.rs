@@ -0,0 +1,67 @@
+use serde::{Deserialize, Deserializer, Serialize, de::Error};
+use wasapi::Direction;
+
+use crate::platform::Discovered;
+
+use super::{AudioNightmare, DeviceRole, DeviceSet};
+
+/// Which role's device each third-party integration should follow.
+///
+/// Can be set in the config for the global default, and in profiles to override it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrationTargets {
+    /// The role whose device ShadowPlay's recorded microphone should follow.
+    ///
+    /// Falls back to `Recording` if nothing sets it.
+    #[serde(default, deserialize_with = "deserialize_recording_role")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shadowplay_follows: Option<DeviceRole>,
+}
+
+/// ShadowPlay only records microphones, so anything but a Recording role is rejected on load.
+fn deserialize_recording_role<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<DeviceRole>, D::Error> {
+    let role = Option::<DeviceRole>::deserialize(deserializer)?;
+    if let Some(role) = &role
+        && Direction::from(role) != Direction::Capture
+    {
+        return Err(D::Error::custom(format!(
+            "ShadowPlay can only follow a Recording role, not {role:?}"
+        )));
+    }
+    Ok(role)
+}
+
+impl IntegrationTargets {
+    /// Lays any targets set in `other` on top of our own.
+    pub fn overlay(&mut self, other: &IntegrationTargets) {
+        if other.shadowplay_follows.is_some() {
+            self.shadowplay_follows = other.shadowplay_follows.clone();
+        }
+    }
+}
+
+impl AudioNightmare {
+    /// Sends the given default devices out to any enabled integrations,
+    /// each following the role chosen for it in `targets`.
+    pub fn dispatch_integrations(
+        &self,
+        current: &DeviceSet<Discovered>,
+        targets: &IntegrationTargets,
+    ) {
+        if let Some(shadowplay) = &self.shadowplay {
+            let role = match &targets.shadowplay_follows {
+                // Without the split, the Media role is always the same device as Recording
+                Some(role) if role.is_media() && !self.split_multimedia_role => {
+                    &DeviceRole::Recording
+                }
+                Some(role) => role,
+                None => &DeviceRole::Recording,
+            };
+            if let Some(device) = current.get_role(role) {
+                shadowplay.microphone_change(&device.guid);
+            }
+        }
+    }
+}
@@ -1,9 +1,11 @@
-use std::collections::BTreeMap;
+use std::{borrow::Cow, collections::BTreeMap};
 
+use derivative::Derivative;
 use devices::WindowsAudioDevice;
 use menu_macro::*;
 use regex_lite::Regex;
 use serde::{Deserialize, Serialize};
+use serde_inline_default::serde_inline_default;
 use shadowplay::ShadowPlayHandle;
 use takeable::Takeable;
 use tracing::*;
@@ -25,12 +27,18 @@ use crate::{
 
 use device_notifications::{NotificationCallbacks, WindowsAudioNotification};
 use policy_config::{IPolicyConfig, PolicyConfig};
+use simulated::{PlatformSnapshot, ResolvedDevice, SimulatedEndpoints};
 
 use super::{ConfigEntry, Discovered};
 
 pub mod device_notifications;
 pub mod devices;
 pub use devices::{ConfigDevice, DeviceRole, DeviceSet, DiscoveredDevice};
+pub mod integrations;
+pub use integrations::IntegrationTargets;
+pub mod simulated;
+pub mod sorting;
+pub use sorting::sorted_devices;
 
 mod device_ser;
 mod policy_config;
@@ -41,7 +49,10 @@ pub struct AudioNightmare {
     /// Interface to query endpoints through
     device_enumerator: Takeable<IMMDeviceEnumerator>,
     /// Interface to change endpoints through
-    policy_config: Takeable<IPolicyConfig>,
+    ///
+    /// `None` if the (undocumented) interface couldn't be created,
+    /// in which case we can only monitor devices, not change them.
+    policy_config: Option<IPolicyConfig>,
     /// Client object for endpoint notifications from Windows
     device_callbacks: Option<NotificationCallbacks>,
     /// Existing devices attached to the host
@@ -55,9 +66,27 @@ pub struct AudioNightmare {
     /// When `true`, *all* actions taken towards the Console/Multimedia Role
     /// will be applied to the Communications Role
     pub unify_communications_devices: bool,
+    /// When `true`, the Multimedia Role is assigned separately from the Console Role
+    /// (via the `*Media` roles), instead of always following it
+    pub split_multimedia_role: bool,
+    /// Devices that win over any profile for their role, whenever they're connected
+    pub pinned_devices: DeviceSet<ConfigEntry>,
+    /// Devices that should never be chosen, even if a profile names them
+    never_use_devices: Vec<DeviceMatcher>,
+    /// When `false`, the Playback (and Playback Media) Role is never changed
+    pub manage_playback: bool,
+    /// When `false`, the Playback Communications Role is never changed
+    pub manage_playback_comms: bool,
+    /// When `false`, the Recording (and Recording Media) Role is never changed
+    pub manage_recording: bool,
+    /// When `false`, the Recording Communications Role is never changed
+    pub manage_recording_comms: bool,
     /// When present, will be used to attempt to keep the ShadowPlay recorded device
-    /// the same as the Default `Recording` device.
+    /// the same as the device of the role chosen in the active `IntegrationTargets`.
     shadowplay: Option<ShadowPlayHandle>,
+    /// When present, default roles are read from and set on this instead of the host,
+    /// since we're replaying an event trace.
+    simulated: Option<SimulatedEndpoints>,
 }
 impl Drop for AudioNightmare {
     fn drop(&mut self) {
@@ -78,6 +107,24 @@ impl AudioNightmare {
     pub fn build(
         event_proxy: Option<AppEventProxy>,
         config: Option<&PlatformSettings>,
+    ) -> AppResult<Self> {
+        Self::build_with(event_proxy, config, None)
+    }
+    /// Builds a handler that works off of the given snapshot instead of the host's devices,
+    /// for replaying an event trace.
+    ///
+    /// Nothing on the host is changed, and no endpoint notifications are listened for,
+    /// since those come from the trace instead.
+    pub fn build_simulated(
+        config: &PlatformSettings,
+        snapshot: PlatformSnapshot,
+    ) -> AppResult<Self> {
+        Self::build_with(None, Some(config), Some(snapshot))
+    }
+    fn build_with(
+        event_proxy: Option<AppEventProxy>,
+        config: Option<&PlatformSettings>,
+        snapshot: Option<PlatformSnapshot>,
     ) -> AppResult<Self> {
         let default = PlatformSettings::default();
         let config = config.unwrap_or(&default);
@@ -86,31 +133,51 @@ impl AudioNightmare {
             CoInitializeEx(None, COINIT_APARTMENTTHREADED).ok()?;
         }
 
-        let policy_config: IPolicyConfig =
-            unsafe { CoCreateInstance(&PolicyConfig, None, CLSCTX_ALL) }?;
+        let simulated = snapshot
+            .as_ref()
+            .map(|snapshot| SimulatedEndpoints::new(snapshot.defaults.clone()));
+
+        // Since this interface is undocumented, it's not guaranteed to exist on every
+        // edition or future build of Windows. If it's missing, we can still show devices.
+        let policy_config: Option<IPolicyConfig> = if simulated.is_some() {
+            None
+        } else {
+            match unsafe { CoCreateInstance(&PolicyConfig, None, CLSCTX_ALL) } {
+                Ok(policy_config) => Some(policy_config),
+                Err(e) => {
+                    error!("Failed to create IPolicyConfig, running in monitor-only mode: {e}");
+                    None
+                }
+            }
+        };
         let device_enumerator: IMMDeviceEnumerator =
             unsafe { CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL) }?;
 
         let mut playback_devices = BTreeMap::new();
         let mut recording_devices = BTreeMap::new();
 
-        let initial_playback = DeviceCollection::new(&Direction::Render)?;
+        if let Some(snapshot) = snapshot {
+            playback_devices = snapshot.playback_devices;
+            recording_devices = snapshot.recording_devices;
+        } else {
+            let initial_playback = DeviceCollection::new(&Direction::Render)?;
 
-        for device in &initial_playback {
-            let device: DiscoveredDevice = device.expect("Couldn't get device").try_into()?;
-            playback_devices.insert(device.guid.clone(), device);
-        }
+            for device in &initial_playback {
+                let device: DiscoveredDevice = device.expect("Couldn't get device").try_into()?;
+                playback_devices.insert(device.guid.clone(), device);
+            }
 
-        // println!("{playback_devices:#?}");
+            // println!("{playback_devices:#?}");
 
-        let initial_recording = DeviceCollection::new(&Direction::Capture)?;
+            let initial_recording = DeviceCollection::new(&Direction::Capture)?;
 
-        for device in &initial_recording {
-            let device: DiscoveredDevice = device.expect("Couldn't get device").try_into()?;
-            recording_devices.insert(device.guid.clone(), device);
-        }
+            for device in &initial_recording {
+                let device: DiscoveredDevice = device.expect("Couldn't get device").try_into()?;
+                recording_devices.insert(device.guid.clone(), device);
+            }
 
-        // println!("{recording_devices:#?}");
+            // println!("{recording_devices:#?}");
+        }
 
         let mut device_callbacks = None;
 
@@ -127,8 +194,15 @@ impl AudioNightmare {
         let regex_windows_numeric_prefix = Regex::new(r" \(\d+- ").expect("Regex failed to build");
 
         let unify_communications_devices = config.unify_communications_devices;
+        let split_multimedia_role = config.split_multimedia_role;
+        let pinned_devices = config.pinned_devices.clone();
+        let never_use_devices = config
+            .never_use_devices
+            .iter()
+            .map(|device| DeviceMatcher::new(device, &regex_windows_numeric_prefix))
+            .collect();
 
-        let shadowplay = if config.shadowplay_support {
+        let shadowplay = if config.shadowplay_support && simulated.is_none() {
             match ShadowPlayHandle::build() {
                 Ok(handle) => Some(handle),
                 Err(e) => {
@@ -141,7 +215,7 @@ impl AudioNightmare {
         };
 
         Ok(Self {
-            policy_config: Takeable::new(policy_config),
+            policy_config,
             device_enumerator: Takeable::new(device_enumerator),
             device_callbacks,
             // callback_rx: rx,
@@ -150,15 +224,41 @@ impl AudioNightmare {
             regex_windows_numeric_prefix,
             event_proxy,
             unify_communications_devices,
+            split_multimedia_role,
+            pinned_devices,
+            never_use_devices,
+            manage_playback: config.manage_playback,
+            manage_playback_comms: config.manage_playback_comms,
+            manage_recording: config.manage_recording,
+            manage_recording_comms: config.manage_recording_comms,
             shadowplay,
+            simulated,
         })
     }
+    /// Returns `true` if we're unable to change default devices on this system.
+    pub fn is_monitor_only(&self) -> bool {
+        self.policy_config.is_none() && self.simulated.is_none()
+    }
     pub fn set_device_role(&self, device_id: &str, role: &Role) -> AppResult<()> {
+        if let Some(simulated) = self.simulated.as_ref() {
+            let direction = if self.playback_devices.contains_key(device_id) {
+                Direction::Render
+            } else {
+                Direction::Capture
+            };
+            let device = self
+                .device_by_guid(&direction, device_id)
+                .cloned()
+                .ok_or_else(|| RedefaulterError::DeviceNotFound(device_id.to_owned()))?;
+            simulated.set_default(&direction, role, Some(device));
+            return Ok(());
+        }
+        let policy_config = self
+            .policy_config
+            .as_ref()
+            .ok_or(RedefaulterError::MonitorOnly)?;
         let wide_id = device_id.to_wide();
-        unsafe {
-            self.policy_config
-                .SetDefaultEndpoint(wide_id.as_pwstr(), role.to_owned().into())
-        }?;
+        unsafe { policy_config.SetDefaultEndpoint(wide_id.as_pwstr(), role.to_owned().into()) }?;
         Ok(())
     }
     pub fn print_devices(&self, categories: &ListSubcommand) {
@@ -179,7 +279,7 @@ impl AudioNightmare {
     fn print_profile_format(&self, playback: bool, recording: bool) {
         if playback {
             println!("Playback devices: ");
-            for device in self.playback_devices.values() {
+            for device in sorted_devices(&self.playback_devices) {
                 println!(
                     "{}",
                     serde_json::to_string(device).expect("Failed to serialize profile")
@@ -192,7 +292,7 @@ impl AudioNightmare {
             }
             println!("Recording devices: ");
 
-            for device in self.recording_devices.values() {
+            for device in sorted_devices(&self.recording_devices) {
                 println!(
                     "{}",
                     serde_json::to_string(device).expect("Failed to serialize profile")
@@ -211,11 +311,11 @@ impl AudioNightmare {
 
         if playback {
             println!("Playback devices: ");
-            for device in &self.playback_devices {
+            for device in sorted_devices(&self.playback_devices) {
                 println!(
                     "{:<width$} - {}",
-                    device.1.human_name,
-                    device.1.guid,
+                    device.human_name,
+                    device.guid,
                     width = max_len
                 );
             }
@@ -226,17 +326,27 @@ impl AudioNightmare {
             }
             println!("Recording devices: ");
 
-            for device in &self.recording_devices {
+            for device in sorted_devices(&self.recording_devices) {
                 println!(
                     "{:<width$} - {}",
-                    device.1.human_name,
-                    device.1.guid,
+                    device.human_name,
+                    device.guid,
                     width = max_len
                 );
             }
         }
     }
     fn add_endpoint(&mut self, id: &str, known_to_be_active: bool) -> AppResult<()> {
+        if let Some(simulated) = self.simulated.as_mut() {
+            // The trace tells us what the endpoint turned out to be, if it was active
+            if let Some(ResolvedDevice { direction, device }) = simulated.pending_endpoint.take()
+                && device.guid == id
+            {
+                info!("New {direction:?} device!");
+                self.insert_endpoint(direction, device);
+            }
+            return Ok(());
+        }
         let id = String::from(id).to_wide();
         let device: IMMDevice = unsafe { self.device_enumerator.GetDevice(id.as_pwstr())? };
         let endpoint: IMMEndpoint = device.cast()?;
@@ -256,7 +366,11 @@ impl AudioNightmare {
         }
 
         let device: DiscoveredDevice = device.try_into()?;
+        self.insert_endpoint(direction, device);
 
+        Ok(())
+    }
+    fn insert_endpoint(&mut self, direction: Direction, device: DiscoveredDevice) {
         match direction {
             Direction::Render => {
                 if let Some(old) = self.playback_devices.insert(device.guid.clone(), device) {
@@ -269,8 +383,6 @@ impl AudioNightmare {
                 };
             }
         }
-
-        Ok(())
     }
     fn remove_endpoint(&mut self, id: &str) {
         if self.playback_devices.remove(id).is_none() {
@@ -286,7 +398,7 @@ impl AudioNightmare {
         match notif {
             DeviceAdded { id } => self.add_endpoint(&id, false)?,
             DeviceRemoved { id } => self.remove_endpoint(&id),
-            DeviceStateChanged { id, state } => match state.0 {
+            DeviceStateChanged { id, state } => match state {
                 // https://learn.microsoft.com/en-us/windows/win32/coreaudio/device-state-xxx-constants
                 // ACTIVE
                 0x1 => self.add_endpoint(&id, true)?,
@@ -294,7 +406,13 @@ impl AudioNightmare {
                 0x2 | 0x4 | 0x8 => self.remove_endpoint(&id),
                 _ => panic!("Got unexpected state from DeviceStateChanged!"),
             },
-            DefaultDeviceChanged { .. } => (),
+            DefaultDeviceChanged { id, flow, role } => {
+                // Windows keeps track of these on its own, unless we're standing in for it
+                if let Some(simulated) = self.simulated.as_ref() {
+                    let device = self.device_by_guid(&flow, &id).cloned();
+                    simulated.set_default(&flow, &role, device);
+                }
+            }
         }
         if let Some(proxy) = self.event_proxy.as_ref() {
             proxy
@@ -303,43 +421,67 @@ impl AudioNightmare {
         }
         Ok(())
     }
-    /// Gets device by name, strict matching
-    fn device_by_name<'a>(
-        &'a self,
-        direction: &Direction,
-        name: &str,
-    ) -> Option<&'a DiscoveredDevice> {
-        if name.is_empty() {
-            return None;
-        }
-        let find = |map: &'a BTreeMap<String, DiscoveredDevice>| -> Option<&'a DiscoveredDevice> {
-            map.values().find(|d| d.human_name == name)
+    /// Returns the device the given notification added, if it was active,
+    /// so an event trace can replay it without asking Windows.
+    pub fn added_endpoint(&self, notif: &WindowsAudioNotification) -> Option<ResolvedDevice> {
+        use WindowsAudioNotification::*;
+        let id = match notif {
+            DeviceAdded { id } | DeviceStateChanged { id, state: 0x1 } => id,
+            _ => return None,
         };
-        match direction {
-            Direction::Render => find(&self.playback_devices),
-            Direction::Capture => find(&self.recording_devices),
+        [Direction::Render, Direction::Capture]
+            .into_iter()
+            .find_map(|direction| {
+                let device = self.device_by_guid(&direction, id)?.clone();
+                Some(ResolvedDevice { direction, device })
+            })
+    }
+    /// Sets what the next added endpoint resolves to when simulating, since we can't ask Windows.
+    pub fn expect_endpoint(&mut self, device: Option<ResolvedDevice>) {
+        if let Some(simulated) = self.simulated.as_mut() {
+            simulated.pending_endpoint = device;
+        }
+    }
+    /// Returns the host's devices and every default role as they are right now,
+    /// for an event trace to start from.
+    pub fn snapshot(&self) -> AppResult<PlatformSnapshot> {
+        let mut defaults = DeviceSet::default();
+        for role in &DeviceRole::ALL {
+            let device = Self::get_default_device_for_role(&role.into(), &role.into())?;
+            defaults.update_role(role, device);
+        }
+        Ok(PlatformSnapshot {
+            playback_devices: self.playback_devices.clone(),
+            recording_devices: self.recording_devices.clone(),
+            defaults,
+        })
+    }
+    /// Returns the name to compare the given device name by,
+    /// with any numeric prefix removed when fuzzy matching.
+    fn match_name<'a>(&self, name: &'a str, fuzzy_match_names: bool) -> Cow<'a, str> {
+        if fuzzy_match_names {
+            self.regex_windows_numeric_prefix.replace(name, " (")
+        } else {
+            Cow::Borrowed(name)
         }
     }
-    /// Gets device by name fuzzily.
+    /// Gets device by name, fuzzily if asked.
     ///
     /// Searches in the specified set of devices,
-    /// returning the first device that matches,
-    /// ignoring numeric prefixes in desired device name or discovered device names.
-    fn device_by_name_fuzzy<'a>(
+    /// returning the first device that matches.
+    fn device_by_name<'a>(
         &'a self,
         direction: &Direction,
-        name: &str,
+        matcher: &DeviceMatcher,
+        fuzzy_match_names: bool,
     ) -> Option<&'a DiscoveredDevice> {
-        if name.is_empty() {
+        if matcher.name.is_empty() {
             return None;
         }
-        let desired_normalized = self.regex_windows_numeric_prefix.replace(name, " (");
         let find = |map: &'a BTreeMap<String, DiscoveredDevice>| -> Option<&'a DiscoveredDevice> {
             map.values().find(|d| {
-                desired_normalized
-                    == self
-                        .regex_windows_numeric_prefix
-                        .replace(&d.human_name, " (")
+                let name = self.match_name(&d.human_name, fuzzy_match_names);
+                matcher.name_matches(&name, fuzzy_match_names)
             })
         };
         match direction {
@@ -347,19 +489,19 @@ impl AudioNightmare {
             Direction::Capture => find(&self.recording_devices),
         }
     }
+    /// Returns `true` if the user has marked the given device as never to be used.
+    pub fn is_never_use(&self, device: &DiscoveredDevice, fuzzy_match_names: bool) -> bool {
+        let name = self.match_name(&device.human_name, fuzzy_match_names);
+        self.never_use_devices
+            .iter()
+            .any(|matcher| matcher.matches(&device.guid, &name, fuzzy_match_names))
+    }
     fn device_by_guid(&self, direction: &Direction, guid: &str) -> Option<&DiscoveredDevice> {
         match direction {
             Direction::Render => self.playback_devices.get(guid),
             Direction::Capture => self.recording_devices.get(guid),
         }
     }
-    pub fn get_role_default(&self, role: &DeviceRole) -> AppResult<DiscoveredDevice> {
-        let target_role: Role = role.into();
-        let target_direction: Direction = role.into();
-        let default_device: DiscoveredDevice =
-            get_default_device_for_role(&target_direction, &target_role)?.try_into()?;
-        Ok(default_device)
-    }
     fn get_default_device_for_role(
         direction: &Direction,
         role: &Role,
@@ -376,11 +518,20 @@ impl AudioNightmare {
             Err(e) => Err(e)?,
         }
     }
-    // Bit of a slow operation, queries Windows for all four roles individually.
+    // Bit of a slow operation, queries Windows for all four (or six) roles individually.
     pub fn get_current_defaults(&self) -> AppResult<DeviceSet<Discovered>> {
         use wasapi::Direction::*;
         use wasapi::Role::*;
 
+        if let Some(simulated) = self.simulated.as_ref() {
+            let mut defaults = simulated.defaults();
+            if !self.split_multimedia_role {
+                defaults.playback_media = None;
+                defaults.recording_media = None;
+            }
+            return Ok(defaults);
+        }
+
         debug!("Getting Playback!");
         let playback = Self::get_default_device_for_role(&Render, &Console)?;
         debug!("Getting Playback Comms!");
@@ -389,25 +540,54 @@ impl AudioNightmare {
         let recording = Self::get_default_device_for_role(&Capture, &Console)?;
         debug!("Getting Recording Comms!");
         let recording_comms = Self::get_default_device_for_role(&Capture, &Communications)?;
-        debug!("Got all default devices!!!");
 
-        // Tacking on ShadowPlay's action here, since this runs not too frequently
-        // (mainly only when devices change),
-        // but enough to not lag behind.
-        // Plus we just got the most recent Recording device, which is the one we want.
-        if let Some(shadowplay) = &self.shadowplay
-            && let Some(recording) = &recording
-        {
-            shadowplay.microphone_change(&recording.guid);
-        }
+        // Only worth asking for when they're treated separately,
+        // otherwise they're kept the same as the Console role.
+        let (playback_media, recording_media) = if self.split_multimedia_role {
+            debug!("Getting Playback Media!");
+            let playback_media = Self::get_default_device_for_role(&Render, &Multimedia)?;
+            debug!("Getting Recording Media!");
+            let recording_media = Self::get_default_device_for_role(&Capture, &Multimedia)?;
+            (playback_media, recording_media)
+        } else {
+            (None, None)
+        };
+        debug!("Got all default devices!!!");
 
         Ok(DeviceSet {
             playback,
+            playback_media,
             playback_comms,
             recording,
+            recording_media,
             recording_comms,
         })
     }
+    /// Returns `true` if the given role is currently assigned on its own.
+    ///
+    /// `*Media` roles only are when split from the Console role,
+    /// and Communications roles aren't when they're unified.
+    pub fn is_role_assignable(&self, role: &DeviceRole) -> bool {
+        if role.is_media() {
+            self.split_multimedia_role
+        } else if role.is_comms() {
+            !self.unify_communications_devices
+        } else {
+            true
+        }
+    }
+    /// Returns `true` if the user allows us to change the given role.
+    ///
+    /// Media roles follow the setting of their base role.
+    pub fn is_role_managed(&self, role: &DeviceRole) -> bool {
+        use DeviceRole::*;
+        match role {
+            Playback | PlaybackMedia => self.manage_playback,
+            PlaybackComms => self.manage_playback_comms,
+            Recording | RecordingMedia => self.manage_recording,
+            RecordingComms => self.manage_recording_comms,
+        }
+    }
     /// Tries to find device by GUID first, and then by name
     pub fn try_find_device(
         &self,
@@ -416,51 +596,104 @@ impl AudioNightmare {
         fuzzy_match_names: bool,
     ) -> Option<&DiscoveredDevice> {
         self.device_by_guid(direction, &needle.guid).or_else(|| {
-            if fuzzy_match_names {
-                self.device_by_name_fuzzy(direction, &needle.human_name)
-            } else {
-                self.device_by_name(direction, &needle.human_name)
-            }
+            let matcher = DeviceMatcher::new(needle, &self.regex_windows_numeric_prefix);
+            self.device_by_name(direction, &matcher, fuzzy_match_names)
         })
     }
+    /// Returns the roles (and their desired devices) from the given set that can't currently be found.
+    ///
+    /// Unmanaged roles, and roles that aren't assigned on their own, are never reported.
+    pub fn find_missing_devices(
+        &self,
+        desired: &DeviceSet<ConfigEntry>,
+        fuzzy_match_names: bool,
+    ) -> Vec<(DeviceRole, ConfigDevice)> {
+        DeviceRole::ALL
+            .into_iter()
+            .filter(|role| self.is_role_managed(role) && self.is_role_assignable(role))
+            .filter_map(|role| {
+                let device = desired.get_role(&role)?;
+                if self
+                    .try_find_device(&(&role).into(), device, fuzzy_match_names)
+                    .is_some()
+                {
+                    None
+                } else {
+                    Some((role, device.clone()))
+                }
+            })
+            .collect()
+    }
     /// Given an input of desired devices from an active profile,
     /// search our lists of known connected and active devices,
     /// and "overlay" the devices we were able to find on top
     /// of the given action set.
+    ///
+    /// Roles the user has marked as unmanaged are left untouched,
+    /// devices marked as never-use are skipped,
+    /// and connected pinned devices win over whatever was desired.
     pub fn overlay_available_devices(
         &self,
         actions: &mut DeviceSet<Discovered>,
         desired: &DeviceSet<ConfigEntry>,
         fuzzy_match_names: bool,
     ) {
-        use wasapi::Direction::*;
-        let update_device = |direction: &Direction,
+        use DeviceRole::*;
+        let update_device = |role: &DeviceRole,
                              role_action_opt: &mut Option<DiscoveredDevice>,
                              desired_opt: Option<&ConfigDevice>| {
-            if let Some(desired) = desired_opt
-                && let Some(device) = self.try_find_device(direction, desired, fuzzy_match_names)
-            {
+            if !self.is_role_managed(role) {
+                return;
+            }
+            let find_usable = |needle: &ConfigDevice| {
+                self.try_find_device(&role.into(), needle, fuzzy_match_names)
+                    .filter(|device| !self.is_never_use(device, fuzzy_match_names))
+            };
+            let pinned = self.pinned_devices.get_role(role).and_then(find_usable);
+            if let Some(device) = pinned.or_else(|| desired_opt.and_then(find_usable)) {
                 _ = role_action_opt.insert(device.clone());
             }
         };
 
-        update_device(&Render, &mut actions.playback, desired.playback.as_ref());
+        update_device(&Playback, &mut actions.playback, desired.playback.as_ref());
+        if self.split_multimedia_role {
+            update_device(
+                &PlaybackMedia,
+                &mut actions.playback_media,
+                desired.playback_media.as_ref(),
+            );
+        }
         if self.unify_communications_devices {
-            actions.playback_comms = actions.playback.clone();
+            if self.is_role_managed(&PlaybackComms) {
+                actions.playback_comms = actions.playback.clone();
+            }
         } else {
             update_device(
-                &Render,
+                &PlaybackComms,
                 &mut actions.playback_comms,
                 desired.playback_comms.as_ref(),
             );
         }
 
-        update_device(&Capture, &mut actions.recording, desired.recording.as_ref());
+        update_device(
+            &Recording,
+            &mut actions.recording,
+            desired.recording.as_ref(),
+        );
+        if self.split_multimedia_role {
+            update_device(
+                &RecordingMedia,
+                &mut actions.recording_media,
+                desired.recording_media.as_ref(),
+            );
+        }
         if self.unify_communications_devices {
-            actions.recording_comms = actions.recording.clone();
+            if self.is_role_managed(&RecordingComms) {
+                actions.recording_comms = actions.recording.clone();
+            }
         } else {
             update_device(
-                &Capture,
+                &RecordingComms,
                 &mut actions.recording_comms,
                 desired.recording_comms.as_ref(),
             );
@@ -474,56 +707,107 @@ impl AudioNightmare {
             }
         };
         clear_if_matching(&mut left.playback, &right.playback);
+        clear_if_matching(&mut left.playback_media, &right.playback_media);
         clear_if_matching(&mut left.playback_comms, &right.playback_comms);
         clear_if_matching(&mut left.recording, &right.recording);
+        clear_if_matching(&mut left.recording_media, &right.recording_media);
         clear_if_matching(&mut left.recording_comms, &right.recording_comms);
     }
+    /// Applies every role in the given set, even if some of them fail.
+    ///
+    /// Returns `RedefaulterError::PartialApply` with each role that couldn't be set.
     pub fn change_devices(&self, new_devices: DeviceSet<Discovered>) -> AppResult<()> {
+        use DeviceRole::{
+            Playback, PlaybackComms, PlaybackMedia, Recording, RecordingComms, RecordingMedia,
+        };
         use Role::*;
+        // Unless the user's split them, Multimedia always follows Console.
+        let console_roles = if self.split_multimedia_role {
+            vec![Console]
+        } else {
+            vec![Console, Multimedia]
+        };
         let roles = [
-            (new_devices.playback, vec![Console, Multimedia]),
-            (new_devices.playback_comms, vec![Communications]),
-            (new_devices.recording, vec![Console, Multimedia]),
-            (new_devices.recording_comms, vec![Communications]),
+            (Playback, new_devices.playback, console_roles.clone()),
+            (PlaybackMedia, new_devices.playback_media, vec![Multimedia]),
+            (
+                PlaybackComms,
+                new_devices.playback_comms,
+                vec![Communications],
+            ),
+            (Recording, new_devices.recording, console_roles),
+            (
+                RecordingMedia,
+                new_devices.recording_media,
+                vec![Multimedia],
+            ),
+            (
+                RecordingComms,
+                new_devices.recording_comms,
+                vec![Communications],
+            ),
         ];
 
-        for (device_opt, roles) in roles.iter() {
+        let mut failures = Vec::new();
+
+        for (device_role, device_opt, roles) in roles {
             if let Some(device) = device_opt
                 && !device.guid.is_empty()
             {
                 info!("Setting {} -> {roles:?}", device.human_name);
                 for role in roles {
-                    self.set_device_role(&device.guid, role)?;
+                    if let Err(e) = self.set_device_role(&device.guid, &role) {
+                        // Only a debug log, since we retry each poll and the App reports any new failures
+                        debug!("Failed to set {} -> {role:?}: {e}", device.human_name);
+                        // One failure is enough to call the whole role broken,
+                        // but we still want to try the rest of the roles.
+                        failures.push((device_role, e));
+                        break;
+                    }
                 }
             }
         }
 
-        Ok(())
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(RedefaulterError::PartialApply(failures))
+        }
     }
     /// Update the Platform handler with the given config
+    ///
+    /// Integrations aren't sent the current devices here,
+    /// call `dispatch_integrations` afterwards for that.
     pub fn update_config(&mut self, config: &PlatformSettings) {
         self.unify_communications_devices = config.unify_communications_devices;
-
-        if config.shadowplay_support {
-            self.shadowplay = match ShadowPlayHandle::build() {
-                Ok(handle) => {
-                    if let Ok(recording) = self.get_role_default(&DeviceRole::Recording) {
-                        handle.microphone_change(&recording.guid);
-                        Some(handle)
-                    } else {
+        self.split_multimedia_role = config.split_multimedia_role;
+        self.manage_playback = config.manage_playback;
+        self.manage_playback_comms = config.manage_playback_comms;
+        self.manage_recording = config.manage_recording;
+        self.manage_recording_comms = config.manage_recording_comms;
+        self.pinned_devices = config.pinned_devices.clone();
+        self.never_use_devices = config
+            .never_use_devices
+            .iter()
+            .map(|device| DeviceMatcher::new(device, &self.regex_windows_numeric_prefix))
+            .collect();
+
+        // Nothing to send ShadowPlay while simulating, the host's devices aren't ours to touch
+        if config.shadowplay_support && self.simulated.is_none() {
+            // Don't want to spin up another actor if we already have one
+            if self.shadowplay.is_none() {
+                self.shadowplay = match ShadowPlayHandle::build() {
+                    Ok(handle) => Some(handle),
+                    Err(e) => {
+                        error!("{e}");
                         None
                     }
-                }
-                Err(e) => {
-                    error!("{e}");
-                    None
-                }
-            };
+                };
+            }
         } else {
             self.shadowplay = None;
         }
     }
-    // Could probably replace this with some generic iterator over the enum variants for `DeviceRole`...
     pub fn copy_all_roles(
         &self,
         source: &DeviceSet<Discovered>,
@@ -531,10 +815,7 @@ impl AudioNightmare {
         save_fuzzy_name: bool,
         save_guid: bool,
     ) {
-        use DeviceRole::*;
-        let roles = [Playback, PlaybackComms, Recording, RecordingComms];
-
-        for role in roles {
+        for role in DeviceRole::ALL {
             let real_device_opt = source.get_role(&role);
             let config_device = real_device_opt
                 .map(|real| self.device_to_config_entry(real, save_fuzzy_name, save_guid));
@@ -567,13 +848,9 @@ impl AudioNightmare {
         save_fuzzy_name: bool,
         save_guid: bool,
     ) -> WindowsAudioDevice<ConfigEntry> {
-        let human_name = if save_fuzzy_name {
-            self.regex_windows_numeric_prefix
-                .replace(&discovered.human_name, " (")
-                .to_string()
-        } else {
-            discovered.human_name.to_owned()
-        };
+        let human_name = self
+            .match_name(&discovered.human_name, save_fuzzy_name)
+            .into_owned();
 
         let guid = if save_guid {
             discovered.guid.to_owned()
@@ -585,7 +862,50 @@ impl AudioNightmare {
     }
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize, MenuToggle, MenuId, TrayChecks)]
+/// A config entry prepared for matching against discovered devices,
+/// so its name only needs normalizing once.
+#[derive(Debug, Clone)]
+struct DeviceMatcher {
+    guid: String,
+    name: String,
+    /// `name` with any numeric prefix removed, for fuzzy matching
+    fuzzy_name: String,
+}
+
+impl DeviceMatcher {
+    fn new(needle: &ConfigDevice, regex_windows_numeric_prefix: &Regex) -> Self {
+        let fuzzy_name = regex_windows_numeric_prefix
+            .replace(&needle.human_name, " (")
+            .into_owned();
+        Self {
+            guid: needle.guid.clone(),
+            name: needle.human_name.clone(),
+            fuzzy_name,
+        }
+    }
+    /// Compares against a name from [`AudioNightmare::match_name`],
+    /// made with the same `fuzzy_match_names`.
+    fn name_matches(&self, match_name: &str, fuzzy_match_names: bool) -> bool {
+        if self.name.is_empty() {
+            return false;
+        }
+        if fuzzy_match_names {
+            self.fuzzy_name == match_name
+        } else {
+            self.name == match_name
+        }
+    }
+    /// Returns `true` if the device is what the config entry describes,
+    /// either by GUID or by name.
+    fn matches(&self, guid: &str, match_name: &str, fuzzy_match_names: bool) -> bool {
+        (!self.guid.is_empty() && self.guid == guid)
+            || self.name_matches(match_name, fuzzy_match_names)
+    }
+}
+
+#[serde_inline_default]
+#[derive(Debug, Clone, Serialize, Deserialize, MenuToggle, MenuId, TrayChecks, Derivative)]
+#[derivative(Default)]
 pub struct PlatformSettings {
     /// Unify Communications Devices
     ///
@@ -599,10 +919,55 @@ pub struct PlatformSettings {
     #[menuid(rename = "shadow")]
     #[serde(default)]
     pub shadowplay_support: bool,
+    /// Separate Multimedia Devices
+    ///
+    /// When true, the Multimedia role gets its own entries in the tray and profiles (`playback_media`/`recording_media`), instead of always following the Default device.
+    #[menuid(rename = "split_media")]
+    #[serde(default)]
+    pub split_multimedia_role: bool,
+    /// Manage Playback
+    ///
+    /// When false, Redefaulter will never change the default Playback (or Playback Media) device.
+    #[serde_inline_default(true)]
+    #[derivative(Default(value = "true"))]
+    pub manage_playback: bool,
+    /// Manage Playback Comm.
+    ///
+    /// When false, Redefaulter will never change the default Playback Communications device.
+    #[serde_inline_default(true)]
+    #[derivative(Default(value = "true"))]
+    pub manage_playback_comms: bool,
+    /// Manage Recording
+    ///
+    /// When false, Redefaulter will never change the default Recording (or Recording Media) device.
+    #[serde_inline_default(true)]
+    #[derivative(Default(value = "true"))]
+    pub manage_recording: bool,
+    /// Manage Recording Comm.
+    ///
+    /// When false, Redefaulter will never change the default Recording Communications device.
+    #[serde_inline_default(true)]
+    #[derivative(Default(value = "true"))]
+    pub manage_recording_comms: bool,
+    /// Which roles integrations (like ShadowPlay) follow when no active profile says otherwise
+    #[menuid(skip)]
+    #[serde(default)]
+    #[serde(flatten)]
+    pub integrations: IntegrationTargets,
+    /// Devices that should never be chosen, even if a profile names them (like virtual cables)
+    #[menuid(skip)]
+    #[serde(default)]
+    #[serde(rename = "never_use")]
+    pub never_use_devices: Vec<ConfigDevice>,
     #[menuid(skip)]
     #[serde(default)]
     #[serde(rename = "default")]
     pub default_devices: DeviceSet<ConfigEntry>,
+    /// Devices that always win over any profile for their role, whenever they're connected
+    #[menuid(skip)]
+    #[serde(default)]
+    #[serde(rename = "pinned")]
+    pub pinned_devices: DeviceSet<ConfigEntry>,
 }
 
 // Yoinked from https://gist.github.com/dgellow/fb85229ee8aeabf3844a5f3d38eb445d
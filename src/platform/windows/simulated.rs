@@ -0,0 +1,59 @@
+use std::{cell::RefCell, collections::BTreeMap};
+
+use serde::{Deserialize, Serialize};
+use wasapi::{Direction, Role};
+
+use crate::platform::Discovered;
+
+use super::{DeviceRole, DeviceSet, DiscoveredDevice, device_notifications::direction_serde};
+
+/// The devices and default roles of the host at a point in time,
+/// used as the starting point when replaying an event trace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlatformSnapshot {
+    pub playback_devices: BTreeMap<String, DiscoveredDevice>,
+    pub recording_devices: BTreeMap<String, DiscoveredDevice>,
+    /// Every role's default device, including the Multimedia roles even if they aren't split
+    pub defaults: DeviceSet<Discovered>,
+}
+
+/// A device Windows told us about, as it was read when the notification was handled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedDevice {
+    #[serde(with = "direction_serde")]
+    pub direction: Direction,
+    pub device: DiscoveredDevice,
+}
+
+/// Stands in for Windows' default roles when replaying an event trace,
+/// so nothing on the host is read or changed.
+#[derive(Debug)]
+pub struct SimulatedEndpoints {
+    /// Behind a `RefCell` since setting a role only takes `&AudioNightmare`, like the real thing
+    defaults: RefCell<DeviceSet<Discovered>>,
+    /// What the next added endpoint resolves to, taken from the trace.
+    ///
+    /// `None` if it wasn't active, in which case it isn't added.
+    pub pending_endpoint: Option<ResolvedDevice>,
+}
+
+impl SimulatedEndpoints {
+    pub fn new(defaults: DeviceSet<Discovered>) -> Self {
+        Self {
+            defaults: RefCell::new(defaults),
+            pending_endpoint: None,
+        }
+    }
+    pub fn defaults(&self) -> DeviceSet<Discovered> {
+        self.defaults.borrow().clone()
+    }
+    pub fn set_default(
+        &self,
+        direction: &Direction,
+        role: &Role,
+        device: Option<DiscoveredDevice>,
+    ) {
+        let role = DeviceRole::from_windows(direction, role);
+        self.defaults.borrow_mut().update_role(&role, device);
+    }
+}
@@ -0,0 +1,50 @@
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use windows::{
+    Win32::Globalization::{
+        CSTR_EQUAL, CSTR_GREATER_THAN, CSTR_LESS_THAN, CompareStringEx, LINGUISTIC_IGNORECASE,
+        SORT_DIGITSASNUMBERS,
+    },
+    core::PCWSTR,
+};
+
+use super::DiscoveredDevice;
+
+/// Compares two names the way Explorer would, using the user's locale.
+///
+/// Digits are compared by their numeric value, so "Speakers (2-...)" comes before "Speakers (10-...)".
+pub fn compare_names(left: &str, right: &str) -> Ordering {
+    let left_wide: Vec<u16> = left.encode_utf16().collect();
+    let right_wide: Vec<u16> = right.encode_utf16().collect();
+    // A null locale name is LOCALE_NAME_USER_DEFAULT
+    let result = unsafe {
+        CompareStringEx(
+            PCWSTR::null(),
+            LINGUISTIC_IGNORECASE | SORT_DIGITSASNUMBERS,
+            &left_wide,
+            &right_wide,
+            None,
+            None,
+            None,
+        )
+    };
+    match result {
+        CSTR_LESS_THAN => Ordering::Less,
+        CSTR_EQUAL => Ordering::Equal,
+        CSTR_GREATER_THAN => Ordering::Greater,
+        // Zero on failure, byte ordering is better than nothing.
+        _ => left.cmp(right),
+    }
+}
+
+/// Returns the given devices sorted by their displayed name.
+///
+/// Falls back to the GUID for devices that compare equal, to keep the order stable.
+pub fn sorted_devices(devices: &BTreeMap<String, DiscoveredDevice>) -> Vec<&DiscoveredDevice> {
+    let mut sorted: Vec<&DiscoveredDevice> = devices.values().collect();
+    sorted.sort_by(|left, right| {
+        compare_names(&left.to_string(), &right.to_string())
+            .then_with(|| left.guid.cmp(&right.guid))
+    });
+    sorted
+}
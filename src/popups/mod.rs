@@ -3,8 +3,19 @@ mod windows;
 #[cfg(target_os = "windows")]
 pub use windows::*;
 
+use serde::{Deserialize, Serialize};
+
 use crate::app::{AppEventProxy, CustomEvent};
 
+/// How a profile created from the tray should be set up.
+#[derive(Debug, Clone, Copy)]
+pub struct NewProfileOptions {
+    /// Save the process' full path instead of just its name
+    pub save_absolute_path: bool,
+    /// Start the profile with the current default devices instead of blank
+    pub from_current_defaults: bool,
+}
+
 #[derive(Debug)]
 pub enum FirstTimeChoice {
     AutoLaunch(bool),
@@ -20,7 +31,24 @@ impl From<FirstTimeChoice> for CustomEvent {
     }
 }
 
-pub fn executable_file_picker(event_proxy: AppEventProxy, save_absolute_path: bool) {
+/// The user's answer to the startup report
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct StartupChoice {
+    /// When `true`, apply the reported changes now.
+    ///
+    /// Otherwise they're skipped, until profiles or devices call for a different change.
+    pub apply: bool,
+    /// When `false`, the user doesn't want to be asked on future launches
+    pub ask_again: bool,
+}
+
+impl From<StartupChoice> for CustomEvent {
+    fn from(value: StartupChoice) -> Self {
+        Self::StartupChoice(value)
+    }
+}
+
+pub fn executable_file_picker(event_proxy: AppEventProxy, options: NewProfileOptions) {
     std::thread::spawn(move || {
         let dialog = rfd::FileDialog::new().set_title("Select path of executable to watch for:");
 
@@ -34,7 +62,7 @@ pub fn executable_file_picker(event_proxy: AppEventProxy, save_absolute_path: bo
             return;
         };
         event_proxy
-            .send_event(CustomEvent::NewProfile(path, save_absolute_path))
+            .send_event(CustomEvent::NewProfile(path, options))
             .unwrap();
     });
 }
@@ -1,5 +1,5 @@
 use std::thread;
-use win_msgbox::{Okay, RetryCancel, YesNoCancel};
+use win_msgbox::{Okay, RetryCancel, YesNo, YesNoCancel};
 
 use crate::{
     app::{App, AppEventProxy, CustomEvent},
@@ -8,7 +8,7 @@ use crate::{
     processes::LockFile,
 };
 
-use super::FirstTimeChoice;
+use super::{FirstTimeChoice, StartupChoice};
 
 #[derive(Debug)]
 pub enum PlatformPrompts {
@@ -65,6 +65,28 @@ pub fn profile_exists_popup(error: RedefaulterError) {
     });
 }
 
+pub fn monitor_only_popup() {
+    thread::spawn(move || {
+        win_msgbox::warning::<Okay>(
+            "Redefaulter is unable to change default audio devices on this system.\n\nThe Windows interface it relies on couldn't be created (this can happen on N editions or newer builds of Windows).\n\nRedefaulter will keep running in monitor-only mode: the tray menu will still show your devices and profiles, but no devices will be switched.",
+        )
+        .title("Redefaulter - Monitor-only Mode")
+        .show()
+        .expect("Couldn't show warning popup!");
+    });
+}
+
+pub fn partial_apply_popup(error: RedefaulterError) {
+    thread::spawn(move || {
+        win_msgbox::warning::<Okay>(&format!(
+            "{error}\n\nRedefaulter will keep retrying, check the logs for more info."
+        ))
+        .title("Redefaulter Error")
+        .show()
+        .expect("Couldn't show warning popup!");
+    });
+}
+
 pub fn settings_load_failed_popup(error: RedefaulterError, lock_file: LockFile) -> ! {
     win_msgbox::error::<Okay>(&format!(
         "{error}\n\nPlease fix the settings file and try again."
@@ -118,6 +140,58 @@ pub fn first_time_popups(
     });
 }
 
+/// Shows the user what we're about to change on startup, letting them apply or skip it,
+/// then asks whether to keep showing this on future launches.
+pub fn startup_report_popup(
+    current_defaults: DeviceSet<Discovered>,
+    actions: DeviceSet<Discovered>,
+    event_proxy: AppEventProxy,
+) {
+    thread::spawn(move || {
+        let changes = format_changes(&current_defaults, &actions);
+        let text = format!(
+            "Redefaulter will change:\n\n{changes}\nApply these changes now?\n\nIf you choose No, these changes will be skipped until your profiles or devices call for a different change."
+        );
+        // Yes/No popups can't be closed without picking one,
+        // so there's no way to apply anything without a clear Yes.
+        let response = win_msgbox::information::<YesNo>(&text)
+            .title("Redefaulter - Startup")
+            .show()
+            .expect("Couldn't show startup popup!");
+        let apply = matches!(response, YesNo::Yes);
+
+        let response = win_msgbox::information::<YesNo>(
+            "Show this prompt again the next time Redefaulter starts?\n\n(This can also be changed in Settings.)",
+        )
+        .title("Redefaulter - Startup")
+        .show()
+        .expect("Couldn't show startup popup!");
+        let ask_again = matches!(response, YesNo::Yes);
+
+        let choice = StartupChoice { apply, ask_again };
+        event_proxy.send_event(choice.into()).unwrap();
+    });
+}
+
+fn format_changes(current: &DeviceSet<Discovered>, actions: &DeviceSet<Discovered>) -> String {
+    let mut buffer = String::new();
+
+    for role in DeviceRole::ALL {
+        let Some(new_device) = actions.get_role(&role) else {
+            continue;
+        };
+        let old_name = current
+            .get_role(&role)
+            .map(|d| d.human_name.as_str())
+            .unwrap_or("N/A (?)");
+        buffer.push_str(&format!(
+            "{role}: {old_name} -> {}\n",
+            new_device.human_name
+        ));
+    }
+    buffer
+}
+
 // Lazy way of doing this, should maybe be part of the set methods?
 fn format_devices(devices: &DeviceSet<Discovered>, unify_example: bool) -> String {
     let mut buffer = String::new();
@@ -3,7 +3,7 @@ use crate::errors::{AppResult, RedefaulterError};
 use crate::profiles::AppOverride;
 
 use dashmap::DashMap;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::mpsc::Sender;
@@ -24,7 +24,7 @@ struct ProcessEvent {
 
 // There's a chance that using PathBuf here might bite me in the ass?
 // https://github.com/serde-rs/json/issues/550
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename = "Win32_Process")]
 #[serde(rename_all = "PascalCase")]
 pub struct Process {
@@ -13,7 +13,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     errors::{AppResult, RedefaulterError},
-    platform::{ConfigEntry, DeviceSet},
+    platform::{ConfigEntry, DeviceSet, IntegrationTargets},
     processes::Process,
 };
 
@@ -23,6 +23,9 @@ pub struct AppOverride {
     pub process_path: PathBuf,
     #[serde(flatten)]
     pub override_set: DeviceSet<ConfigEntry>,
+    #[serde(default)]
+    #[serde(flatten)]
+    pub integrations: IntegrationTargets,
 }
 
 // TODO, app icon variants for these
@@ -167,6 +170,7 @@ impl Profiles {
         &mut self,
         process_path: PathBuf,
         save_absolute_path: bool,
+        override_set: DeviceSet<ConfigEntry>,
     ) -> AppResult<()> {
         let Some(process_name) = process_path.file_name() else {
             return Err(RedefaulterError::ProfileEmptyProcessPath(
@@ -197,6 +201,7 @@ impl Profiles {
 
         let new_override = AppOverride {
             process_path: output_path,
+            override_set,
             ..Default::default()
         };
 
@@ -238,6 +243,13 @@ impl Profiles {
             .iter()
             .map(|p| &self.inner.get(p).unwrap().override_set)
     }
+    pub fn iter_active_integration_targets(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &IntegrationTargets> {
+        self.active
+            .iter()
+            .map(|p| &self.inner.get(p).unwrap().integrations)
+    }
     pub fn iter_active_profiles(
         &self,
     ) -> impl DoubleEndedIterator<Item = (&OsString, &AppOverride)> {
@@ -299,6 +311,7 @@ impl From<DeviceSet<ConfigEntry>> for AppOverride {
         Self {
             process_path: PathBuf::new(),
             override_set: value,
+            integrations: IntegrationTargets::default(),
         }
     }
 }
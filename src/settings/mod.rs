@@ -52,6 +52,19 @@ pub struct DeviceSettings {
     /// Just a toggle for showing the current default devices in the tray menu.
     #[serde(default)]
     pub show_active: bool,
+    /// Apply Devices When Connected
+    ///
+    /// When true, if an active profile's device isn't connected,
+    /// Redefaulter will watch for it and switch to it the moment it appears,
+    /// instead of waiting for the next device check.
+    #[serde(default)]
+    pub wait_for_devices: bool,
+    /// Confirm Changes on Startup
+    ///
+    /// When true, shows what devices will be changed when Redefaulter launches,
+    /// and lets you apply or skip them instead of switching right away.
+    #[serde(default)]
+    pub confirm_on_startup: bool,
     /// Platform-specific settings, including preferred default devices.
     #[menuid(skip)]
     #[serde(default)]
@@ -82,6 +95,10 @@ pub struct MiscSettings {
     pub log_level: String,
     #[serde(default)]
     pub first_time_setup_done: bool,
+    /// When true, internal events are recorded to a file next to the log,
+    /// to help reproduce bugs.
+    #[serde(default)]
+    pub record_events: bool,
 }
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize, MenuToggle, MenuId, TrayChecks)]
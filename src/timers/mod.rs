@@ -0,0 +1,134 @@
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Names for each deadline the event loop can wait on.
+///
+/// Only one deadline per name can be pending at a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum TimerId {
+    /// Periodic check of the current default devices
+    DevicePoll,
+}
+
+/// A small collection of named deadlines, so several features can share
+/// the event loop's single `ControlFlow::WaitUntil`.
+#[derive(Debug)]
+pub struct Timers<Id = TimerId> {
+    deadlines: BTreeMap<Id, Instant>,
+}
+
+// Derived Default would needlessly require `Id: Default`
+impl<Id> Default for Timers<Id> {
+    fn default() -> Self {
+        Self {
+            deadlines: BTreeMap::new(),
+        }
+    }
+}
+
+impl<Id: Ord + Copy> Timers<Id> {
+    /// Sets the deadline for the given timer, replacing any pending one.
+    pub fn schedule_at(&mut self, id: Id, deadline: Instant) {
+        self.deadlines.insert(id, deadline);
+    }
+    /// Sets the given timer to fire after `delay`, replacing any pending deadline.
+    pub fn schedule_in(&mut self, id: Id, delay: Duration) {
+        self.schedule_at(id, Instant::now() + delay);
+    }
+    /// Returns the soonest pending deadline, if any.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.deadlines.values().min().copied()
+    }
+    /// Removes and returns every timer whose deadline has been reached,
+    /// in order of their deadlines.
+    pub fn pop_expired(&mut self, now: Instant) -> Vec<Id> {
+        let mut expired: Vec<(Id, Instant)> = self
+            .deadlines
+            .iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(id, deadline)| (*id, *deadline))
+            .collect();
+        expired.sort_by_key(|(_, deadline)| *deadline);
+
+        for (id, _) in &expired {
+            self.deadlines.remove(id);
+        }
+
+        expired.into_iter().map(|(id, _)| id).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    enum TestId {
+        First,
+        Second,
+        Third,
+    }
+
+    #[test]
+    fn expired_in_deadline_order() {
+        let start = Instant::now();
+        let mut timers = Timers::default();
+        // Scheduled out of order on purpose, both by id and by deadline
+        timers.schedule_at(TestId::First, start + Duration::from_secs(2));
+        timers.schedule_at(TestId::Third, start + Duration::from_secs(1));
+        timers.schedule_at(TestId::Second, start + Duration::from_secs(3));
+
+        assert_eq!(timers.next_deadline(), Some(start + Duration::from_secs(1)));
+        assert_eq!(
+            timers.pop_expired(start + Duration::from_secs(3)),
+            vec![TestId::Third, TestId::First, TestId::Second]
+        );
+        assert_eq!(timers.next_deadline(), None);
+    }
+
+    #[test]
+    fn reschedule_replaces_deadline() {
+        let start = Instant::now();
+        let mut timers = Timers::default();
+        timers.schedule_at(TestId::First, start + Duration::from_secs(1));
+        timers.schedule_at(TestId::First, start + Duration::from_secs(5));
+
+        assert_eq!(timers.next_deadline(), Some(start + Duration::from_secs(5)));
+        assert!(
+            timers
+                .pop_expired(start + Duration::from_secs(1))
+                .is_empty()
+        );
+        assert_eq!(
+            timers.pop_expired(start + Duration::from_secs(5)),
+            vec![TestId::First]
+        );
+    }
+
+    #[test]
+    fn pending_timers_are_kept() {
+        let start = Instant::now();
+        let mut timers = Timers::default();
+        timers.schedule_at(TestId::First, start + Duration::from_secs(1));
+        timers.schedule_at(TestId::Second, start + Duration::from_secs(10));
+
+        assert!(timers.pop_expired(start).is_empty());
+        assert_eq!(
+            timers.pop_expired(start + Duration::from_secs(1)),
+            vec![TestId::First]
+        );
+        // The later timer is still pending, and fires once it's due
+        assert_eq!(
+            timers.next_deadline(),
+            Some(start + Duration::from_secs(10))
+        );
+        assert_eq!(
+            timers.pop_expired(start + Duration::from_secs(10)),
+            vec![TestId::Second]
+        );
+    }
+}
@@ -11,8 +11,8 @@ use tray_icon::{
 use crate::{
     app::App,
     errors::AppResult,
-    platform::{ConfigDevice, DeviceRole, DiscoveredDevice},
-    popups::executable_file_picker,
+    platform::{ConfigDevice, DeviceRole, DiscoveredDevice, sorted_devices},
+    popups::{NewProfileOptions, executable_file_picker},
     profiles::{AppOverride, PROFILES_PATH, TempOverride},
     tray_menu::TrayDevice,
     updates::UpdateState,
@@ -26,6 +26,8 @@ pub mod common_ids {
 
     pub const NEW_SAVE_NAME: &str = "new-name";
     pub const NEW_SAVE_PATH: &str = "new-path";
+    pub const NEW_CURRENT_SAVE_NAME: &str = "new-current-name";
+    pub const NEW_CURRENT_SAVE_PATH: &str = "new-current-path";
 
     pub const DISABLE_OVERRIDE_ID: &str = "override-disable";
     pub const PAUSE_OVERRIDE_ID: &str = "override-pause";
@@ -81,12 +83,28 @@ impl App {
 
         Ok(handle)
     }
+    fn failed_roles_text(&self) -> String {
+        self.failed_roles
+            .iter()
+            .map(|role| role.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
     pub fn kill_tray_menu(&mut self) -> Option<TrayIcon> {
         self.tray_menu.take()
     }
     pub fn update_tray_menu(&self) -> AppResult<()> {
         if let Some(handle) = self.tray_menu.as_ref() {
             let post_text = match &self.update_state {
+                UpdateState::Idle if self.awaiting_startup_choice => {
+                    "Awaiting startup confirmation".to_string()
+                }
+                UpdateState::Idle if !self.failed_roles.is_empty() => {
+                    format!("Failed to set {}", self.failed_roles_text())
+                }
+                UpdateState::Idle if self.endpoints.is_monitor_only() => {
+                    "Monitor-only mode".to_string()
+                }
                 UpdateState::Idle => {
                     let active_len = self.profiles.active_len();
                     if active_len == 1 {
@@ -126,6 +144,24 @@ impl App {
             }
         }
 
+        if self.endpoints.is_monitor_only() {
+            menu.append(&label_item("Monitor-only mode, devices won't be changed"))?;
+            menu.append(&PredefinedMenuItem::separator())?;
+        }
+
+        if self.awaiting_startup_choice {
+            menu.append(&label_item(
+                "Awaiting startup confirmation, devices won't be changed",
+            ))?;
+            menu.append(&PredefinedMenuItem::separator())?;
+        }
+
+        if !self.failed_roles.is_empty() {
+            let text = format!("Failed to set {} (retrying)", self.failed_roles_text());
+            menu.append(&label_item(text))?;
+            menu.append(&PredefinedMenuItem::separator())?;
+        }
+
         if self.settings.devices.show_active {
             let active_devices = self.tray_platform_active_devices()?;
             let item_refs = active_devices
@@ -419,18 +455,15 @@ impl App {
                 self.settings.save(&self.config_path)?;
                 self.endpoints
                     .update_config(&self.settings.devices.platform);
+                // Splitting the Multimedia role changes which roles we query,
+                // (and also sends the integrations the current devices)
+                self.update_defaults()?;
 
                 // Since we don't want to wait for another event to make us check for this later.
+                // (Unifying or (un)managing a role can both change what we want the devices to be)
                 #[cfg(windows)]
-                if id
-                    == self
-                        .settings
-                        .devices
-                        .platform
-                        .unify_communications_devices_menu_id()
-                {
+                if id != self.settings.devices.platform.shadowplay_support_menu_id() {
                     self.change_devices_if_needed()?;
-                    self.update_tray_menu()?;
                 }
 
                 self.update_tray_menu()?;
@@ -444,6 +477,7 @@ impl App {
             _ if id.starts_with(self.settings.devices.menu_id_root()) => {
                 self.settings.devices.handle_menu_toggle_event(id)?;
                 self.settings.save(&self.config_path)?;
+                self.arm_device_watcher();
                 self.update_tray_menu()?;
             }
             IGNORE_ID => {
@@ -523,11 +557,15 @@ impl App {
                 }
                 _ => error!("Invalid update menu command!"),
             },
-            NEW_SAVE_NAME => {
-                executable_file_picker(self.event_proxy.clone(), false);
-            }
-            NEW_SAVE_PATH => {
-                executable_file_picker(self.event_proxy.clone(), true);
+            NEW_SAVE_NAME | NEW_SAVE_PATH | NEW_CURRENT_SAVE_NAME | NEW_CURRENT_SAVE_PATH => {
+                let options = NewProfileOptions {
+                    save_absolute_path: matches!(id, NEW_SAVE_PATH | NEW_CURRENT_SAVE_PATH),
+                    from_current_defaults: matches!(
+                        id,
+                        NEW_CURRENT_SAVE_NAME | NEW_CURRENT_SAVE_PATH
+                    ),
+                };
+                executable_file_picker(self.event_proxy.clone(), options);
             }
             AUTO_LAUNCH_ID => {
                 let auto_launch_enabled = self.get_auto_launch_enabled()?;
@@ -538,6 +576,20 @@ impl App {
         }
         Ok(())
     }
+    /// Returns `false` for menu items that reach outside the app
+    /// (opening folders or pickers, updates, auto-launch), which a replayed trace shouldn't click.
+    pub fn is_replayable_menu_id(&self, id: &str) -> bool {
+        let reaches_outside = matches!(
+            id,
+            REVEAL_ID
+                | AUTO_LAUNCH_ID
+                | NEW_SAVE_NAME
+                | NEW_SAVE_PATH
+                | NEW_CURRENT_SAVE_NAME
+                | NEW_CURRENT_SAVE_PATH
+        );
+        !reaches_outside && !id.starts_with(UPDATE_PREFIX)
+    }
     /// Takes in a deserialized device click event, modifies the specified profile, and saves the relevant file.
     fn handle_tray_device_selection(&mut self, tray_device: TrayDevice) -> AppResult<()> {
         let set_to_modify = match &tray_device.destination {
@@ -598,6 +650,22 @@ impl App {
                 None,
             ))
             .build()?;
+        let new_current_profile = SubmenuBuilder::new()
+            .enabled(true)
+            .text("Save Current Defaults as New Profile...")
+            .item(&MenuItem::with_id(
+                NEW_CURRENT_SAVE_NAME,
+                "...with Process Name",
+                true,
+                None,
+            ))
+            .item(&MenuItem::with_id(
+                NEW_CURRENT_SAVE_PATH,
+                "...with Full Process Path",
+                true,
+                None,
+            ))
+            .build()?;
         let reload = MenuItem::with_id(RELOAD_ID, "&Reload Profiles", true, None);
         let reveal = MenuItem::with_id(REVEAL_ID, "Reveal Profiles &Folder", true, None);
         let settings_submenu = self.build_tray_settings_submenu()?;
@@ -606,6 +674,7 @@ impl App {
         menu.append_items(&[
             &PredefinedMenuItem::separator(),
             &new_profile,
+            &new_current_profile,
             &reload,
             &reveal,
             &PredefinedMenuItem::separator(),
@@ -647,7 +716,7 @@ pub fn build_device_checks(
 
     let mut device_found = false;
 
-    for device in all_devices.values() {
+    for device in sorted_devices(all_devices) {
         let tray_device = TrayDevice::new(selection_type, role, &device.guid);
         let chosen = if let Some(current) = current_as_discovered.as_ref() {
             device_found = true;
@@ -14,7 +14,6 @@ impl App {
     // so I'm just gonna keep it off by default.
     pub fn tray_platform_active_devices(&self) -> AppResult<Vec<Box<dyn IsMenuItem>>> {
         let mut devices: Vec<Box<dyn IsMenuItem>> = Vec::new();
-        use DeviceRole::*;
 
         let header = label_item("Active Devices:");
         devices.push(Box::new(header));
@@ -34,16 +33,11 @@ impl App {
             label_item(text)
         };
 
-        devices.push(Box::new(build_device(&Playback)));
-
-        if !self.settings.devices.platform.unify_communications_devices {
-            devices.push(Box::new(build_device(&PlaybackComms)));
-        }
-
-        devices.push(Box::new(build_device(&Recording)));
-
-        if !self.settings.devices.platform.unify_communications_devices {
-            devices.push(Box::new(build_device(&RecordingComms)));
+        for role in DeviceRole::ALL
+            .iter()
+            .filter(|role| self.endpoints.is_role_assignable(role))
+        {
+            devices.push(Box::new(build_device(role)));
         }
 
         Ok(devices)
@@ -55,33 +49,14 @@ impl App {
     ) -> AppResult<Vec<Box<dyn IsMenuItem>>> {
         let mut submenus: Vec<Box<dyn IsMenuItem>> = Vec::new();
 
-        use DeviceRole::*;
-
-        submenus.push(Box::new(self.tray_build_platform_device_selection(
-            destination,
-            &Playback,
-            device_set.playback.as_ref(),
-        )?));
-
-        if !self.settings.devices.platform.unify_communications_devices {
-            submenus.push(Box::new(self.tray_build_platform_device_selection(
-                destination,
-                &PlaybackComms,
-                device_set.playback_comms.as_ref(),
-            )?));
-        }
-
-        submenus.push(Box::new(self.tray_build_platform_device_selection(
-            destination,
-            &Recording,
-            device_set.recording.as_ref(),
-        )?));
-
-        if !self.settings.devices.platform.unify_communications_devices {
+        for role in DeviceRole::ALL
+            .iter()
+            .filter(|role| self.endpoints.is_role_assignable(role))
+        {
             submenus.push(Box::new(self.tray_build_platform_device_selection(
                 destination,
-                &RecordingComms,
-                device_set.recording_comms.as_ref(),
+                role,
+                device_set.get_role(role),
             )?));
         }
 